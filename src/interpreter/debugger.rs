@@ -0,0 +1,283 @@
+use super::{Environment, EvaluationError, EvaluationResult, Expr, Interpreter, Parser, Scanner, Stmt};
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+const HELP: &str = "\
+break LINE     (b)   pause whenever a statement on LINE starts
+delete LINE    (d)   remove the breakpoint on LINE
+continue       (c)   run until the next breakpoint
+step           (s)   run to the next line, entering calls
+next           (n)   run to the next line in this function
+finish         (f)   run until this function returns
+backtrace      (bt)  list the calls being made
+locals         (l)   list the local variables in scope
+print EXPR     (p)   evaluate an expression where the program is paused
+quit           (q)   stop the program";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepMode {
+    Continue,
+    StepIn,
+    // Pause once the call depth is at most, or below, the one stepping started at
+    StepOver(usize),
+    StepOut(usize),
+}
+
+/// A call being made, with the line of its statement that is running.
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    line: usize,
+}
+
+/// Pauses a running script at breakpoints and after steps, and runs commands
+/// to inspect it.  The interpreter calls `enter` and `leave` around each call to
+/// a Lox function and checks before each statement whether to pause.
+///
+/// A script starts paused at its first statement.  When the commands run out
+/// the script runs to the end without pausing again.
+pub struct Debugger {
+    source: Vec<String>,
+    commands: Box<dyn BufRead>,
+    breakpoints: BTreeSet<usize>,
+    mode: StepMode,
+    // The top level script is the first frame
+    frames: Vec<Frame>,
+    // The call depth and line of the last statement started, which can't pause
+    // again until one on another line starts
+    previous: Option<(usize, usize)>,
+}
+
+impl std::fmt::Debug for Debugger {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Debugger")
+            .field("breakpoints", &self.breakpoints)
+            .field("mode", &self.mode)
+            .field("frames", &self.frames)
+            .finish()
+    }
+}
+
+impl Debugger {
+    pub fn new(source: &str, commands: Box<dyn BufRead>) -> Debugger {
+        Debugger {
+            source: source.lines().map(String::from).collect(),
+            commands,
+            breakpoints: BTreeSet::new(),
+            mode: StepMode::StepIn,
+            frames: vec![Frame { name: String::from("script"), line: 0 }],
+            previous: None,
+        }
+    }
+
+    pub fn enter(&mut self, name: &str) {
+        self.frames.push(Frame { name: name.to_string(), line: 0 });
+    }
+
+    pub fn leave(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Records a statement starting on a line, returning whether to pause before it.
+    fn arrive(&mut self, line: usize) -> bool {
+        let depth = self.frames.len();
+        if let Some(frame) = self.frames.last_mut() {
+            frame.line = line;
+        }
+        if self.previous.replace((depth, line)) == Some((depth, line)) {
+            return false;
+        }
+
+        self.breakpoints.contains(&line) || match self.mode {
+            StepMode::Continue => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver(start) => depth <= start,
+            StepMode::StepOut(start) => depth < start,
+        }
+    }
+
+    /// Reads and runs commands until one resumes the script.
+    /// Returns whether the user asked to stop the script instead.
+    fn pause(&mut self, interpreter: &mut Interpreter, line: usize) -> bool {
+        let frame = self.frames.last().map_or("script", |frame| &frame.name);
+        println!("Paused in {} at line {}", frame, line);
+        self.print_line(line);
+
+        loop {
+            print!("(debug) ");
+            let _ = io::stdout().flush();
+            let mut command = String::new();
+            if self.commands.read_line(&mut command).unwrap_or(0) == 0 {
+                // Without a way to resume later, the script runs to the end
+                println!();
+                self.breakpoints.clear();
+                self.mode = StepMode::Continue;
+                return false;
+            }
+
+            let command = command.trim();
+            let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+            let argument = argument.trim();
+            let depth = self.frames.len();
+            match name {
+                "b" | "break" => match argument.parse::<usize>() {
+                    Ok(line) => {
+                        self.breakpoints.insert(line);
+                        println!("Breakpoint at line {}", line);
+                    },
+                    Err(_) => println!("Usage: break LINE"),
+                },
+                "d" | "delete" => match argument.parse::<usize>() {
+                    Ok(line) if self.breakpoints.remove(&line) => println!("Removed the breakpoint at line {}", line),
+                    Ok(line) => println!("No breakpoint at line {}", line),
+                    Err(_) => println!("Usage: delete LINE"),
+                },
+                "c" | "continue" => {
+                    self.mode = StepMode::Continue;
+                    return false;
+                },
+                "s" | "step" => {
+                    self.mode = StepMode::StepIn;
+                    return false;
+                },
+                "n" | "next" => {
+                    self.mode = StepMode::StepOver(depth);
+                    return false;
+                },
+                "f" | "finish" => {
+                    self.mode = StepMode::StepOut(depth);
+                    return false;
+                },
+                "bt" | "backtrace" => {
+                    for (i, frame) in self.frames.iter().rev().enumerate() {
+                        println!("#{} {} at line {}", i, frame.name, frame.line);
+                    }
+                },
+                "l" | "locals" => print_locals(interpreter),
+                "p" | "print" => match interpreter.evaluate_in_frame(argument) {
+                    Ok(value) => println!("{}", value),
+                    Err(e) => println!("{}", e),
+                },
+                "q" | "quit" => return true,
+                "h" | "help" => println!("{}", HELP),
+                "" => (),
+                _ => println!("Unknown command {}, try 'help'", name),
+            }
+        }
+    }
+
+    fn print_line(&self, line: usize) {
+        if let Some(text) = line.checked_sub(1).and_then(|index| self.source.get(index)) {
+            println!("{:>4} | {}", line, text);
+        }
+    }
+}
+
+/// Prints the variables of every scope between the paused statement and the globals,
+/// innermost first.
+fn print_locals(interpreter: &Interpreter) {
+    let mut environment = interpreter.environment.clone();
+    let mut found = false;
+    while !Rc::ptr_eq(&environment, &interpreter.globals) {
+        let values = environment.borrow().values();
+        // Names that aren't identifiers are the interpreter's own
+        for (name, value) in values.iter().filter(|(name, _)| !name.starts_with('#')) {
+            println!("{} = {}", name, value);
+            found = true;
+        }
+        let enclosing = environment.borrow().enclosing.clone();
+        environment = match enclosing {
+            Some(enclosing) => enclosing,
+            None => break,
+        };
+    }
+    if !found {
+        println!("No local variables");
+    }
+}
+
+/// The distance from an environment to the scope defining a name,
+/// or None if it's a global.
+fn distance(environment: &Rc<RefCell<Environment>>, globals: &Rc<RefCell<Environment>>, name: &str) -> Option<usize> {
+    let mut environment = environment.clone();
+    let mut distance = 0;
+    while !Rc::ptr_eq(&environment, globals) {
+        if environment.borrow().contains(name) {
+            return Some(distance);
+        }
+        let enclosing = environment.borrow().enclosing.clone()?;
+        environment = enclosing;
+        distance += 1;
+    }
+    None
+}
+
+impl Interpreter {
+    pub fn set_debugger(&mut self, debugger: Option<Debugger>) {
+        self.debugger = debugger;
+    }
+
+    /// Pauses before a statement if the debugger says to.  The debugger is taken
+    /// out while paused so that evaluating expressions doesn't reach it.
+    /// Quitting raises an error that unwinds the whole script.
+    pub fn debug_stmt(&mut self, stmt: &Stmt) -> EvaluationResult<()> {
+        let line = match stmt.line() {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        if let Some(mut debugger) = self.debugger.take() {
+            let quit = debugger.arrive(line) && debugger.pause(self, line);
+            if quit {
+                return Err(EvaluationError::Quit);
+            }
+            self.debugger = Some(debugger);
+        }
+        Ok(())
+    }
+
+    /// Evaluates an expression typed in the debugger, looking its variables up
+    /// from the paused statement's scope.
+    fn evaluate_in_frame(&mut self, source: &str) -> Result<String, String> {
+        let tokens = Scanner::new(source).scan_tokens().map_err(|e| e.message)?;
+        let mut expr = Parser::new(tokens).parse_expression().map_err(|e| e.to_string())?;
+        self.bind(&mut expr);
+        let value = self.evaluate_expr(&expr).map_err(|e| e.to_string())?;
+        self.stringify(&value).map_err(|e| e.to_string())
+    }
+
+    /// Sets the scope distances the resolver would have, from the scopes the
+    /// program is paused in.
+    fn bind(&self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal(_) => (),
+            Expr::Var(name, slot) | Expr::This(name, slot) => {
+                *slot = distance(&self.environment, &self.globals, &name.lexeme);
+            },
+            Expr::Super(keyword, _, slot) => {
+                *slot = distance(&self.environment, &self.globals, &keyword.lexeme);
+            },
+            Expr::Assign(name, value, slot) => {
+                self.bind(value);
+                *slot = distance(&self.environment, &self.globals, &name.lexeme);
+            },
+            Expr::Grouping(expr) | Expr::Unary(_, expr) | Expr::Get(expr, _) => self.bind(expr),
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) | Expr::Set(left, _, right) => {
+                self.bind(left);
+                self.bind(right);
+            },
+            Expr::Call(callee, _, arguments, named) => {
+                self.bind(callee);
+                arguments.iter_mut().for_each(|argument| self.bind(argument));
+                named.iter_mut().for_each(|(_, argument)| self.bind(argument));
+            },
+            Expr::Yield(_, value) => {
+                if let Some(value) = value {
+                    self.bind(value);
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use super::{Destructure, Expr, Literal, MatchArm, Param, Pattern, Stmt, Token};
+
+/// Lists tokens one per line with their line, type, lexeme and literal value.
+pub fn dump_tokens(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| match &token.literal {
+        Some(literal) => format!("[line {}] {:?} {} = {}\n", token.line, token.token_type, token.lexeme, literal),
+        None => format!("[line {}] {:?} {}\n", token.line, token.token_type, token.lexeme),
+    }).collect()
+}
+
+/// Prints statements as an indented tree, one node per line.
+/// With `resolved`, variables show the scope distance the resolver gave them.
+pub fn dump_tree(stmts: &[Stmt], resolved: bool) -> String {
+    let mut out = String::new();
+    for stmt in stmts {
+        stmt_node(stmt, resolved).tree(None, 0, &mut out);
+    }
+    out
+}
+
+/// Prints statements as a JSON array of nodes.
+/// With `resolved`, variables show the scope distance the resolver gave them.
+pub fn dump_json(stmts: &[Stmt], resolved: bool) -> String {
+    let nodes: Vec<Node> = stmts.iter().map(|stmt| stmt_node(stmt, resolved)).collect();
+    let mut out = String::new();
+    json_list(&nodes, 0, &mut out);
+    out.push('\n');
+    out
+}
+
+enum Value {
+    Text(String),
+    Number(String),
+    Bool(bool),
+    Nil,
+    // A resolved scope distance, None for globals
+    Distance(Option<usize>),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn tree(&self) -> String {
+        match self {
+            Value::Text(text) => format!("{:?}", text),
+            Value::Number(number) => number.clone(),
+            Value::Bool(b) => b.to_string(),
+            Value::Nil => "nil".to_string(),
+            Value::Distance(Some(distance)) => distance.to_string(),
+            Value::Distance(None) => "global".to_string(),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(Value::tree).collect();
+                format!("[{}]", values.join(", "))
+            }
+        }
+    }
+
+    fn json(&self) -> String {
+        match self {
+            Value::Text(text) => json_string(text),
+            Value::Number(number) => number.clone(),
+            Value::Bool(b) => b.to_string(),
+            Value::Nil | Value::Distance(None) => "null".to_string(),
+            Value::Distance(Some(distance)) => distance.to_string(),
+            Value::List(values) => {
+                let values: Vec<String> = values.iter().map(Value::json).collect();
+                format!("[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+enum Child {
+    Node(Node),
+    List(Vec<Node>),
+}
+
+struct Node {
+    kind: &'static str,
+    attributes: Vec<(&'static str, Value)>,
+    children: Vec<(&'static str, Child)>,
+}
+
+impl Node {
+    fn new(kind: &'static str) -> Node {
+        Node {
+            kind,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn attribute(mut self, name: &'static str, value: Value) -> Node {
+        self.attributes.push((name, value));
+        self
+    }
+
+    fn text(self, name: &'static str, text: &str) -> Node {
+        self.attribute(name, Value::Text(text.to_string()))
+    }
+
+    fn line(self, token: &Token) -> Node {
+        self.attribute("line", Value::Number(token.line.to_string()))
+    }
+
+    /// Adds a scope distance, shown only in resolved dumps.
+    fn distance(self, distance: Option<usize>, resolved: bool) -> Node {
+        if resolved {
+            self.attribute("distance", Value::Distance(distance))
+        } else {
+            self
+        }
+    }
+
+    fn child(mut self, name: &'static str, node: Node) -> Node {
+        self.children.push((name, Child::Node(node)));
+        self
+    }
+
+    fn optional(self, name: &'static str, node: Option<Node>) -> Node {
+        match node {
+            Some(node) => self.child(name, node),
+            None => self
+        }
+    }
+
+    fn list(mut self, name: &'static str, nodes: Vec<Node>) -> Node {
+        self.children.push((name, Child::List(nodes)));
+        self
+    }
+
+    fn tree(&self, label: Option<&str>, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        if let Some(label) = label {
+            out.push_str(label);
+            out.push_str(": ");
+        }
+        out.push_str(self.kind);
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(" {}={}", name, value.tree()));
+        }
+        out.push('\n');
+
+        for (name, child) in &self.children {
+            match child {
+                Child::Node(node) => node.tree(Some(name), depth + 1, out),
+                Child::List(nodes) if nodes.is_empty() => (),
+                Child::List(nodes) => {
+                    out.push_str(&format!("{}{}:\n", "  ".repeat(depth + 1), name));
+                    nodes.iter().for_each(|node| node.tree(None, depth + 2, out));
+                }
+            }
+        }
+    }
+
+    fn json(&self, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth + 1);
+        out.push_str(&format!("{{\n{}\"type\": {}", indent, json_string(self.kind)));
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(",\n{}{}: {}", indent, json_string(name), value.json()));
+        }
+        for (name, child) in &self.children {
+            out.push_str(&format!(",\n{}{}: ", indent, json_string(name)));
+            match child {
+                Child::Node(node) => node.json(depth + 1, out),
+                Child::List(nodes) => json_list(nodes, depth + 1, out),
+            }
+        }
+        out.push_str(&format!("\n{}}}", "  ".repeat(depth)));
+    }
+}
+
+fn json_list(nodes: &[Node], depth: usize, out: &mut String) {
+    if nodes.is_empty() {
+        out.push_str("[]");
+        return;
+    }
+    let indent = "  ".repeat(depth + 1);
+    out.push('[');
+    for (i, node) in nodes.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        out.push_str(&indent);
+        node.json(depth + 1, out);
+    }
+    out.push_str(&format!("\n{}]", "  ".repeat(depth)));
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn literal_value(literal: &Literal) -> Value {
+    match literal {
+        Literal::String(string) => Value::Text(string.clone()),
+        Literal::Int(n) => Value::Number(n.to_string()),
+        Literal::Num(n) if n.is_finite() => Value::Number(format!("{:?}", n)),
+        Literal::Bool(b) => Value::Bool(*b),
+        Literal::Nil => Value::Nil,
+        // Big integers, decimals and infinite floats aren't JSON numbers
+        literal => Value::Text(literal.to_string()),
+    }
+}
+
+fn names(tokens: &[Token]) -> Value {
+    Value::List(tokens.iter().map(|token| Value::Text(token.lexeme.clone())).collect())
+}
+
+fn stmt_node(stmt: &Stmt, resolved: bool) -> Node {
+    let expr = |expr: &Expr| expr_node(expr, resolved);
+    let stmts = |stmts: &[Stmt]| stmts.iter().map(|stmt| stmt_node(stmt, resolved)).collect();
+
+    match stmt {
+        Stmt::ExprStmt(expression) => Node::new("ExprStmt").child("expression", expr(expression)),
+        Stmt::PrintStmt(keyword, value) => Node::new("PrintStmt").line(keyword).child("value", expr(value)),
+        Stmt::VarDecl(name, initializer) => Node::new("VarDecl")
+            .text("name", &name.lexeme)
+            .line(name)
+            .optional("initializer", initializer.as_ref().map(expr)),
+        Stmt::VarDestructure(pattern, value) => destructure_node("VarDestructure", pattern)
+            .child("value", expr(value)),
+        Stmt::AssignDestructure(pattern, value, distances) => {
+            let mut node = destructure_node("AssignDestructure", pattern);
+            if resolved {
+                node = node.attribute("distances", Value::List(distances.iter().map(|d| Value::Distance(*d)).collect()));
+            }
+            node.child("value", expr(value))
+        },
+        Stmt::ConstDecl(name, value) => Node::new("ConstDecl")
+            .text("name", &name.lexeme)
+            .line(name)
+            .child("value", expr(value)),
+        Stmt::Block(body) => Node::new("Block").list("statements", stmts(body)),
+        Stmt::If(condition, then, else_stmt) => Node::new("If")
+            .child("condition", expr(condition))
+            .child("then", stmt_node(then, resolved))
+            .optional("else", else_stmt.as_ref().map(|else_stmt| stmt_node(else_stmt, resolved))),
+        Stmt::While(condition, body) => Node::new("While")
+            .child("condition", expr(condition))
+            .child("body", stmt_node(body, resolved)),
+        Stmt::ForIn(_, name, iterable, body) => Node::new("ForIn")
+            .text("name", &name.lexeme)
+            .line(name)
+            .child("iterable", expr(iterable))
+            .child("body", stmt_node(body, resolved)),
+        Stmt::Function(name, params, body) => Node::new("Function")
+            .text("name", &name.lexeme)
+            .line(name)
+            .list("params", params.iter().map(|param| param_node(param, resolved)).collect())
+            .list("body", stmts(body)),
+        Stmt::Return(keyword, value) => Node::new("Return")
+            .line(keyword)
+            .optional("value", value.as_ref().map(expr)),
+        Stmt::ClassDecl(name, methods, superclass, traits) => Node::new("ClassDecl")
+            .text("name", &name.lexeme)
+            .line(name)
+            .optional("superclass", superclass.as_ref().map(expr))
+            .list("traits", traits.iter().map(expr).collect())
+            .list("methods", stmts(methods)),
+        Stmt::TraitDecl(name, methods) => Node::new("TraitDecl")
+            .text("name", &name.lexeme)
+            .line(name)
+            .list("methods", stmts(methods)),
+        Stmt::EnumDecl(name, variants) => Node::new("EnumDecl")
+            .text("name", &name.lexeme)
+            .line(name)
+            .list("variants", variants.iter().map(|(variant, fields)| Node::new("Variant")
+                .text("name", &variant.lexeme)
+                .line(variant)
+                .attribute("fields", names(fields))
+            ).collect()),
+        Stmt::Match(keyword, subject, arms) => Node::new("Match")
+            .line(keyword)
+            .child("subject", expr(subject))
+            .list("arms", arms.iter().map(|arm| arm_node(arm, resolved)).collect()),
+        Stmt::Assert(keyword, condition, message, source) => Node::new("Assert")
+            .text("source", source)
+            .line(keyword)
+            .child("condition", expr(condition))
+            .optional("message", message.as_deref().map(expr)),
+        Stmt::Test(keyword, name, body) => Node::new("Test")
+            .text("name", name)
+            .line(keyword)
+            .list("body", stmts(body)),
+    }
+}
+
+fn destructure_node(kind: &'static str, pattern: &Destructure) -> Node {
+    let (open, kind_name, names_list) = match pattern {
+        Destructure::Tuple(open, names_list) => (open, "tuple", names_list),
+        Destructure::Fields(open, names_list) => (open, "fields", names_list),
+    };
+    Node::new(kind)
+        .text("pattern", kind_name)
+        .attribute("names", names(names_list))
+        .line(open)
+}
+
+fn param_node(param: &Param, resolved: bool) -> Node {
+    Node::new("Param")
+        .text("name", &param.name.lexeme)
+        .attribute("rest", Value::Bool(param.rest))
+        .line(&param.name)
+        .optional("default", param.default.as_ref().map(|default| expr_node(default, resolved)))
+}
+
+fn arm_node(arm: &MatchArm, resolved: bool) -> Node {
+    Node::new("MatchArm")
+        .list("patterns", arm.patterns.iter().map(|pattern| pattern_node(pattern, resolved)).collect())
+        .optional("guard", arm.guard.as_ref().map(|guard| expr_node(guard, resolved)))
+        .child("body", stmt_node(&arm.body, resolved))
+}
+
+fn pattern_node(pattern: &Pattern, resolved: bool) -> Node {
+    match pattern {
+        Pattern::Literal(token, literal) => Node::new("LiteralPattern")
+            .attribute("value", literal_value(literal))
+            .line(token),
+        Pattern::Wildcard(token) => Node::new("WildcardPattern").line(token),
+        Pattern::Binding(name) => Node::new("BindingPattern").text("name", &name.lexeme).line(name),
+        Pattern::Class(name, class, fields) => Node::new("ClassPattern")
+            .text("name", &name.lexeme)
+            .attribute("fields", names(fields))
+            .line(name)
+            .child("class", expr_node(class, resolved)),
+        Pattern::Variant(name, variant, enum_expr, fields) => Node::new("VariantPattern")
+            .text("enum", &name.lexeme)
+            .text("variant", &variant.lexeme)
+            .attribute("fields", fields.as_deref().map(names).unwrap_or(Value::Nil))
+            .line(variant)
+            .child("enum", expr_node(enum_expr, resolved)),
+    }
+}
+
+fn expr_node(expr: &Expr, resolved: bool) -> Node {
+    let child = |expr: &Expr| expr_node(expr, resolved);
+
+    match expr {
+        Expr::Literal(literal) => Node::new("Literal").attribute("value", literal_value(literal)),
+        Expr::Grouping(inner) => Node::new("Grouping").child("expression", child(inner)),
+        Expr::Unary(operator, right) => Node::new("Unary")
+            .text("operator", &operator.lexeme)
+            .line(operator)
+            .child("right", child(right)),
+        Expr::Binary(left, operator, right) | Expr::Logical(left, operator, right) => {
+            let kind = if matches!(expr, Expr::Binary(_, _, _)) { "Binary" } else { "Logical" };
+            Node::new(kind)
+                .text("operator", &operator.lexeme)
+                .line(operator)
+                .child("left", child(left))
+                .child("right", child(right))
+        },
+        Expr::Var(name, distance) => Node::new("Var")
+            .text("name", &name.lexeme)
+            .line(name)
+            .distance(*distance, resolved),
+        Expr::Assign(name, value, distance) => Node::new("Assign")
+            .text("name", &name.lexeme)
+            .line(name)
+            .distance(*distance, resolved)
+            .child("value", child(value)),
+        Expr::Call(callee, paren, arguments, named) => Node::new("Call")
+            .line(paren)
+            .child("callee", child(callee))
+            .list("arguments", arguments.iter().map(child).collect())
+            .list("named", named.iter().map(|(name, value)| Node::new("NamedArgument")
+                .text("name", &name.lexeme)
+                .line(name)
+                .child("value", child(value))
+            ).collect()),
+        Expr::Get(object, name) => Node::new("Get")
+            .text("name", &name.lexeme)
+            .line(name)
+            .child("object", child(object)),
+        Expr::Set(object, name, value) => Node::new("Set")
+            .text("name", &name.lexeme)
+            .line(name)
+            .child("object", child(object))
+            .child("value", child(value)),
+        Expr::This(keyword, distance) => Node::new("This")
+            .line(keyword)
+            .distance(*distance, resolved),
+        Expr::Super(keyword, method, distance) => Node::new("Super")
+            .text("method", &method.lexeme)
+            .line(keyword)
+            .distance(*distance, resolved),
+        Expr::Yield(keyword, value) => Node::new("Yield")
+            .line(keyword)
+            .optional("value", value.as_deref().map(child)),
+    }
+}
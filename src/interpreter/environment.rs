@@ -1,14 +1,16 @@
 use super::{Token,LoxType};
 use super::{EvaluationError, EvaluationResult};
-use super::native::funcs::Clock;
+use super::native::funcs::{Clock, Int, Float, BigInt, Decimal, Range, List, Str,
+    Type, ClassOf, Fields, HasField, GetField, SetField, Methods, SuperclassOf};
 
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Environment {
     values: HashMap<String,LoxType>,
+    constants: HashSet<String>,
     pub enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
@@ -22,6 +24,7 @@ impl Environment {
     pub fn new() -> Environment {
         Environment {
             values: HashMap::new(),
+            constants: HashSet::new(),
             enclosing: None,
         }
     }
@@ -35,16 +38,80 @@ impl Environment {
     pub fn from(env: Rc<RefCell<Environment>>) -> Environment {
         Environment { 
             values: HashMap::new(),
+            constants: HashSet::new(),
             enclosing: Some(env)
         }
     }
 
     fn fill_globals(&mut self) {
+        // Natives are constants so scripts can't overwrite them
         // Defines clock function
-        self.define(
+        self.define_constant(
             "clock",
             &LoxType::Func(Rc::new(Clock::new()))
         );
+        // Numeric conversion functions
+        self.define_constant(
+            "int",
+            &LoxType::Func(Rc::new(Int::new()))
+        );
+        self.define_constant(
+            "float",
+            &LoxType::Func(Rc::new(Float::new()))
+        );
+        self.define_constant(
+            "bigint",
+            &LoxType::Func(Rc::new(BigInt::new()))
+        );
+        self.define_constant(
+            "decimal",
+            &LoxType::Func(Rc::new(Decimal::new()))
+        );
+        self.define_constant(
+            "range",
+            &LoxType::Func(Rc::new(Range::new()))
+        );
+        self.define_constant(
+            "list",
+            &LoxType::Func(Rc::new(List::new()))
+        );
+        self.define_constant(
+            "str",
+            &LoxType::Func(Rc::new(Str::new()))
+        );
+        // Reflection functions
+        self.define_constant(
+            "type",
+            &LoxType::Func(Rc::new(Type::new()))
+        );
+        self.define_constant(
+            "classOf",
+            &LoxType::Func(Rc::new(ClassOf::new()))
+        );
+        self.define_constant(
+            "fields",
+            &LoxType::Func(Rc::new(Fields::new()))
+        );
+        self.define_constant(
+            "hasField",
+            &LoxType::Func(Rc::new(HasField::new()))
+        );
+        self.define_constant(
+            "getField",
+            &LoxType::Func(Rc::new(GetField::new()))
+        );
+        self.define_constant(
+            "setField",
+            &LoxType::Func(Rc::new(SetField::new()))
+        );
+        self.define_constant(
+            "methods",
+            &LoxType::Func(Rc::new(Methods::new()))
+        );
+        self.define_constant(
+            "superclassOf",
+            &LoxType::Func(Rc::new(SuperclassOf::new()))
+        );
     }
 
     pub fn define(&mut self, name: &str, value: &LoxType) {
@@ -52,6 +119,30 @@ impl Environment {
         self.values.insert(name.to_string(), value.clone());
     }
 
+    pub fn define_constant(&mut self, name: &str, value: &LoxType) {
+        self.define(name, value);
+        self.constants.insert(name.to_string());
+    }
+
+    pub fn names(&self) -> HashSet<String> {
+        self.values.keys().cloned().collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// The names defined in this scope and their values, sorted by name.
+    pub fn values(&self) -> Vec<(String, LoxType)> {
+        let mut values: Vec<_> = self.values.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        values.sort_by(|(a, _), (b, _)| a.cmp(b));
+        values
+    }
+
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
     pub fn get(&self, token: &Token) -> EvaluationResult<LoxType> {
         if let Some(val) = self.values.get(&token.lexeme) {
             return Ok(val.clone());
@@ -78,6 +169,10 @@ impl Environment {
     }
 
     pub fn assign(&mut self, token: &Token, value: LoxType) -> EvaluationResult<LoxType> {
+        if self.constants.contains(&token.lexeme) {
+            return Err(EvaluationError::AssignToConstant(token.clone()));
+        }
+
         if let Some(val) = self.values.get_mut(&token.lexeme) {
             *val = value;
             return Ok(val.clone());
@@ -10,14 +10,41 @@ pub enum Expr {
     Var(Token, Option<usize>),
     Assign(Token, Box<Expr>, Option<usize>),
     Logical(Box<Expr>, Token, Box<Expr>),
-    Call(Box<Expr>, Token, Vec<Expr>),
+    Call(Box<Expr>, Token, Vec<Expr>, Vec<(Token, Expr)>),
     Get(Box<Expr>, Token),
     Set(Box<Expr>, Token, Box<Expr>),
     This(Token, Option<usize>),
-    Super(Token, Token, Option<usize>)
+    Super(Token, Token, Option<usize>),
+    Yield(Token, Option<Box<Expr>>),
 
 }
 
+impl Expr {
+    /// The expression's leftmost token, or None for literals, which don't keep theirs.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            Expr::Literal(_) => None,
+            Expr::Grouping(expr) => expr.token(),
+            Expr::Binary(left, operator, _) |
+            Expr::Logical(left, operator, _) => left.token().or(Some(operator)),
+            Expr::Call(callee, token, _, _) |
+            Expr::Get(callee, token) |
+            Expr::Set(callee, token, _) => callee.token().or(Some(token)),
+            Expr::Unary(token, _) |
+            Expr::Var(token, _) |
+            Expr::Assign(token, _, _) |
+            Expr::This(token, _) |
+            Expr::Super(token, _, _) |
+            Expr::Yield(token, _) => Some(token),
+        }
+    }
+
+    /// The line of the expression's leftmost token, or None for literals.
+    pub fn line(&self) -> Option<usize> {
+        self.token().map(|token| token.line)
+    }
+}
+
 impl std::fmt::Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
@@ -32,13 +59,18 @@ impl std::fmt::Display for Expr {
             Expr::Logical(ref left, ref operator, ref right) => {
                 write!(f, "({} {} {})", operator.lexeme, left, right)
             }
-            Expr::Call(ref callee, _, ref arguments) => {
+            Expr::Call(ref callee, _, ref arguments, ref named) if named.is_empty() => {
                 write!(f, "(call {} {:?})", callee, arguments)
             }
+            Expr::Call(ref callee, _, ref arguments, ref named) => {
+                write!(f, "(call {} {:?} {:?})", callee, arguments, named)
+            }
             Expr::Get(ref expr, ref token) => write!(f, "(get {} {})", token.lexeme, expr),
             Expr::Set(ref expr, ref token, _) => write!(f, "(set {} {})", token.lexeme, expr),
             Expr::This(_, _) => write!(f, "this"),
             Expr::Super(_, ref method, _) => write!(f, "(super {})", method.lexeme),
+            Expr::Yield(_, Some(ref value)) => write!(f, "(yield {})", value),
+            Expr::Yield(_, None) => write!(f, "(yield)"),
         }
     }
 }
@@ -0,0 +1,603 @@
+use super::{Destructure, Expr, MatchArm, Param, Pattern, Stmt, Token};
+use super::scanner::Comment;
+use super::token::TokenType;
+
+const INDENT: &str = "    ";
+
+/// Prints a parsed program back as canonical Lox source.
+///
+/// The statements decide the layout while the text of every token comes from
+/// the token list, which the printer walks in step with the statements.  This
+/// keeps what the syntax tree doesn't record, such as how a number was written,
+/// `for` loops the parser turned into `while` loops, and comments.
+pub struct Formatter<'a> {
+    tokens: &'a [Token],
+    comments: &'a [Comment],
+    // Index of the next token to print
+    current: usize,
+    // Index of the next comment to print
+    next_comment: usize,
+    output: String,
+    indent: usize,
+    // Whether nothing has been written on the current output line yet
+    at_line_start: bool,
+    // Source line of the last token or comment printed
+    last_line: usize,
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(tokens: &'a [Token], comments: &'a [Comment]) -> Formatter<'a> {
+        Formatter {
+            tokens,
+            comments,
+            current: 0,
+            next_comment: 0,
+            output: String::new(),
+            indent: 0,
+            at_line_start: true,
+            last_line: 1,
+        }
+    }
+
+    /// Formats a whole program, which must have been parsed from the formatter's tokens.
+    pub fn format(mut self, stmts: &[Stmt]) -> String {
+        self.statements(stmts);
+        self.leading_comments();
+        self.output
+    }
+
+    fn statements(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.leading_comments();
+            self.blank_line_before(self.peek().line);
+            self.statement(stmt);
+            self.end_line();
+        }
+    }
+
+    /// Prints a statement, leaving the output line open for a trailing comment or `else`.
+    fn statement(&mut self, stmt: &Stmt) {
+        // The parser turns `for` loops into blocks and `while` loops
+        if self.check(TokenType::For) && !matches!(stmt, Stmt::ForIn(_, _, _, _)) {
+            return self.for_statement(stmt);
+        }
+
+        match stmt {
+            Stmt::ExprStmt(expr) => {
+                self.expression(expr);
+                self.token();
+            },
+            Stmt::PrintStmt(_, expr) => {
+                self.keyword();
+                self.expression(expr);
+                self.token();
+            },
+            Stmt::VarDecl(_, value) => {
+                self.keyword();
+                self.token();
+                if let Some(value) = value {
+                    self.operator();
+                    self.expression(value);
+                }
+                self.token();
+            },
+            Stmt::VarDestructure(pattern, value) => {
+                self.keyword();
+                self.destructure(pattern);
+                self.operator();
+                self.expression(value);
+                self.token();
+            },
+            Stmt::AssignDestructure(pattern, value, _) => {
+                self.destructure(pattern);
+                self.operator();
+                self.expression(value);
+                self.token();
+            },
+            Stmt::ConstDecl(_, value) => {
+                self.keyword();
+                self.token();
+                self.operator();
+                self.expression(value);
+                self.token();
+            },
+            Stmt::Block(stmts) => self.block(stmts),
+            Stmt::If(condition, then, else_stmt) => {
+                self.keyword();
+                self.parenthesized(condition);
+                self.body(then);
+                if let Some(else_stmt) = else_stmt {
+                    if matches!(**then, Stmt::Block(_)) {
+                        self.write(" ");
+                    } else {
+                        self.end_line();
+                    }
+                    self.token();
+                    self.body(else_stmt);
+                }
+            },
+            Stmt::While(condition, body) => {
+                self.keyword();
+                self.parenthesized(condition);
+                self.body(body);
+            },
+            Stmt::ForIn(_, _, iterable, body) => {
+                self.keyword();
+                self.token();
+                if self.check(TokenType::Var) {
+                    self.keyword();
+                }
+                self.token();
+                self.write(" ");
+                self.keyword();
+                self.expression(iterable);
+                self.token();
+                self.body(body);
+            },
+            Stmt::Function(_, params, body) => {
+                if self.check(TokenType::Fun) {
+                    self.keyword();
+                }
+                self.token();
+                self.parameters(params);
+                self.write(" ");
+                self.block(body);
+            },
+            Stmt::Return(_, value) => {
+                self.token();
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.expression(value);
+                }
+                self.token();
+            },
+            Stmt::ClassDecl(_, methods, superclass, traits) => {
+                self.keyword();
+                self.token();
+                if superclass.is_some() {
+                    self.operator();
+                    self.token();
+                }
+                if !traits.is_empty() {
+                    self.write(" ");
+                    self.keyword();
+                    self.list(traits.len(), |formatter, _| formatter.token());
+                }
+                self.write(" ");
+                self.block(methods);
+            },
+            Stmt::TraitDecl(_, methods) => {
+                self.keyword();
+                self.token();
+                self.write(" ");
+                self.block(methods);
+            },
+            Stmt::EnumDecl(_, variants) => self.enum_declaration(variants),
+            Stmt::Match(_, subject, arms) => {
+                self.keyword();
+                self.parenthesized(subject);
+                self.write(" ");
+                self.token();
+                self.indent += 1;
+                for arm in arms {
+                    self.end_line();
+                    self.leading_comments();
+                    self.match_arm(arm);
+                }
+                self.end_line();
+                self.close_block();
+            },
+            Stmt::Assert(_, condition, message, _) => {
+                self.keyword();
+                self.expression(condition);
+                if let Some(message) = message {
+                    self.comma();
+                    self.expression(message);
+                }
+                self.token();
+            },
+            Stmt::Test(_, _, body) => {
+                self.keyword();
+                self.token();
+                self.write(" ");
+                self.block(body);
+            },
+        }
+    }
+
+    /// Prints a `for` loop from the statements the parser built for it:
+    /// a block holding the initializer and a `while` loop, whose body is a
+    /// block ending with the increment.
+    fn for_statement(&mut self, stmt: &Stmt) {
+        self.keyword();
+        self.token();
+
+        let (initializer, while_loop) = match stmt {
+            Stmt::Block(stmts) if !self.check(TokenType::Semicolon) => (Some(&stmts[0]), &stmts[1]),
+            _ => (None, stmt),
+        };
+        let (condition, body) = match while_loop {
+            Stmt::While(condition, body) => (condition, body),
+            _ => unreachable!("for loops are parsed into while loops")
+        };
+
+        match initializer {
+            Some(initializer) => self.statement(initializer),
+            None => self.token(),
+        }
+        if !self.check(TokenType::Semicolon) {
+            self.write(" ");
+            self.expression(condition);
+        }
+        self.token();
+
+        let body = match body.as_ref() {
+            Stmt::Block(stmts) if !self.check(TokenType::RightParen) => match stmts.as_slice() {
+                [body, Stmt::ExprStmt(increment)] => {
+                    self.write(" ");
+                    self.expression(increment);
+                    body
+                },
+                _ => unreachable!("for loop increments follow the body")
+            },
+            body => body,
+        };
+        self.token();
+        self.body(body);
+    }
+
+    /// Prints the body of an `if`, loop or match arm after a space.
+    fn body(&mut self, stmt: &Stmt) {
+        self.write(" ");
+        self.statement(stmt);
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) {
+        self.token();
+        if stmts.is_empty() && self.only_inline_comments() {
+            self.token();
+            return;
+        }
+        self.indent += 1;
+        if !stmts.is_empty() || self.has_comments_before(self.current) {
+            self.end_line();
+        }
+        self.statements(stmts);
+        self.close_block();
+    }
+
+    /// Whether an empty block holds nothing but block comments on the line
+    /// of its braces, which then stay on one line.
+    fn only_inline_comments(&self) -> bool {
+        let close = self.peek();
+        close.token_type == TokenType::RightBrace && close.line == self.last_line && self.comments[self.next_comment..]
+            .iter()
+            .take_while(|comment| comment.token_index <= self.current)
+            .all(|comment| comment.text.starts_with("/*") && comment.line == close.line && !comment.text.contains('\n'))
+    }
+
+    /// Prints the comments left in a block and its closing brace.
+    fn close_block(&mut self) {
+        self.leading_comments();
+        self.indent -= 1;
+        self.token();
+    }
+
+    fn enum_declaration(&mut self, variants: &[(Token, Vec<Token>)]) {
+        self.keyword();
+        self.token();
+        self.write(" ");
+        self.token();
+
+        // Variants with fields go on their own lines, plain variants on one line
+        let one_line = variants.iter().all(|(_, fields)| fields.is_empty());
+        if variants.is_empty() {
+            return self.token();
+        }
+        if one_line {
+            self.write(" ");
+        } else {
+            self.indent += 1;
+        }
+        for (i, (_, fields)) in variants.iter().enumerate() {
+            if !one_line {
+                self.end_line();
+                self.leading_comments();
+            }
+            self.token();
+            if !fields.is_empty() {
+                self.token();
+                self.list(fields.len(), |formatter, _| formatter.token());
+                self.token();
+            }
+            if self.check(TokenType::Comma) {
+                if one_line && i == variants.len() - 1 {
+                    self.skip();
+                } else {
+                    self.token();
+                }
+            }
+            if one_line && i < variants.len() - 1 {
+                self.write(" ");
+            }
+        }
+
+        if one_line {
+            self.write(" ");
+            self.token();
+        } else {
+            if !self.output.ends_with(',') {
+                self.write(",");
+            }
+            self.end_line();
+            self.close_block();
+        }
+    }
+
+    fn match_arm(&mut self, arm: &MatchArm) {
+        self.keyword();
+        self.list(arm.patterns.len(), |formatter, i| formatter.pattern(&arm.patterns[i]));
+        if let Some(guard) = &arm.guard {
+            self.write(" ");
+            self.keyword();
+            self.expression(guard);
+        }
+        self.operator();
+        self.statement(&arm.body);
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Literal(_, _) => {
+                if self.check(TokenType::Minus) {
+                    self.token();
+                }
+                self.token();
+            },
+            Pattern::Wildcard(_) | Pattern::Binding(_) => self.token(),
+            Pattern::Class(_, _, fields) => {
+                self.token();
+                self.token();
+                self.list(fields.len(), |formatter, _| formatter.token());
+                self.token();
+            },
+            Pattern::Variant(_, _, _, fields) => {
+                self.token();
+                self.token();
+                self.token();
+                if let Some(fields) = fields {
+                    self.token();
+                    self.list(fields.len(), |formatter, _| formatter.token());
+                    self.token();
+                }
+            },
+        }
+    }
+
+    fn destructure(&mut self, pattern: &Destructure) {
+        let names = match pattern {
+            Destructure::Tuple(_, names) | Destructure::Fields(_, names) => names.len(),
+        };
+        self.token();
+        self.list(names, |formatter, _| formatter.token());
+        self.token();
+    }
+
+    fn parameters(&mut self, params: &[Param]) {
+        self.token();
+        self.list(params.len(), |formatter, i| {
+            if params[i].rest {
+                formatter.token();
+            }
+            formatter.token();
+            if let Some(default) = &params[i].default {
+                formatter.operator();
+                formatter.expression(default);
+            }
+        });
+        self.token();
+    }
+
+    fn parenthesized(&mut self, expr: &Expr) {
+        self.token();
+        self.expression(expr);
+        self.token();
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) | Expr::Var(_, _) | Expr::This(_, _) => self.token(),
+            Expr::Grouping(inner) => self.parenthesized(inner),
+            Expr::Unary(_, right) => {
+                self.token();
+                self.expression(right);
+            },
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.expression(left);
+                self.operator();
+                self.expression(right);
+            },
+            Expr::Assign(_, value, _) => {
+                self.token();
+                self.operator();
+                self.expression(value);
+            },
+            Expr::Call(callee, _, arguments, named) => {
+                self.expression(callee);
+                self.token();
+                self.list(arguments.len() + named.len(), |formatter, i| match arguments.get(i) {
+                    Some(argument) => formatter.expression(argument),
+                    None => {
+                        formatter.token();
+                        formatter.token();
+                        formatter.write(" ");
+                        formatter.expression(&named[i - arguments.len()].1);
+                    }
+                });
+                self.token();
+            },
+            Expr::Get(object, _) => {
+                self.expression(object);
+                self.token();
+                self.token();
+            },
+            Expr::Set(object, _, value) => {
+                self.expression(object);
+                self.token();
+                self.token();
+                self.operator();
+                self.expression(value);
+            },
+            Expr::Super(_, _, _) => {
+                self.token();
+                self.token();
+                self.token();
+            },
+            Expr::Yield(_, value) => {
+                self.token();
+                if let Some(value) = value {
+                    self.write(" ");
+                    self.expression(value);
+                }
+            },
+        }
+    }
+
+    /// Prints `count` items with `item`, separated by the commas between them.
+    fn list(&mut self, count: usize, mut item: impl FnMut(&mut Self, usize)) {
+        for i in 0..count {
+            if i > 0 {
+                self.comma();
+            }
+            item(self, i);
+        }
+    }
+
+    fn comma(&mut self) {
+        self.token();
+        self.write(" ");
+    }
+
+    /// Prints a keyword followed by a space.
+    fn keyword(&mut self) {
+        self.token();
+        self.write(" ");
+    }
+
+    /// Prints a binary operator surrounded by spaces.
+    fn operator(&mut self) {
+        self.write(" ");
+        self.token();
+        self.write(" ");
+    }
+
+    /// Prints the next token, after any comments that come before it.
+    fn token(&mut self) {
+        self.comments_before(self.current);
+        let token = &self.tokens[self.current];
+        self.write(&token.lexeme);
+        self.last_line = token.line;
+        self.current += 1;
+    }
+
+    /// Moves past a token that the canonical form leaves out.
+    fn skip(&mut self) {
+        self.comments_before(self.current);
+        self.current += 1;
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    fn check(&self, token_type: TokenType) -> bool {
+        self.check_ahead(0, token_type)
+    }
+
+    fn check_ahead(&self, n: usize, token_type: TokenType) -> bool {
+        self.tokens.get(self.current + n).is_some_and(|token| token.token_type == token_type)
+    }
+
+    fn has_comments_before(&self, token_index: usize) -> bool {
+        self.comments.get(self.next_comment).is_some_and(|comment| comment.token_index <= token_index)
+    }
+
+    /// Prints the comments before a token.  A comment starting on the line of
+    /// the token before it stays on that line, others get lines of their own.
+    fn comments_before(&mut self, token_index: usize) {
+        while self.has_comments_before(token_index) {
+            let comment = &self.comments[self.next_comment];
+            self.next_comment += 1;
+
+            let trailing = comment.line == self.last_line && !self.at_line_start && self.current > 0;
+            if trailing {
+                // The token before may have been followed by a space already
+                if !self.output.ends_with(' ') {
+                    self.write(" ");
+                }
+            } else {
+                if !self.at_line_start {
+                    self.newline();
+                }
+                self.blank_line_before(comment.line);
+            }
+            self.write(&comment.text);
+            self.last_line = comment.line + comment.text.matches('\n').count();
+
+            let line_comment = !comment.text.starts_with("/*");
+            if line_comment || !trailing {
+                self.newline();
+            } else {
+                self.write(" ");
+            }
+        }
+    }
+
+    /// Prints the comments before the next token on lines of their own.
+    fn leading_comments(&mut self) {
+        if !self.at_line_start {
+            return;
+        }
+        self.comments_before(self.current);
+    }
+
+    /// Ends the current output line, keeping a comment that follows on the same source line.
+    fn end_line(&mut self) {
+        if let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.token_index <= self.current && comment.line == self.last_line && !self.at_line_start {
+                self.comments_before(self.current);
+            }
+        }
+        if !self.at_line_start {
+            self.newline();
+        }
+    }
+
+    /// Keeps one blank line where the source had one or more, except at the start of a block.
+    fn blank_line_before(&mut self, line: usize) {
+        let block_start = self.output.is_empty() || self.output.ends_with("{\n");
+        if line > self.last_line + 1 && !block_start && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.at_line_start {
+            if text == " " {
+                return;
+            }
+            self.output.push_str(&INDENT.repeat(self.indent));
+            self.at_line_start = false;
+        }
+        self.output.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        // A space written before the line was broken isn't needed
+        while self.output.ends_with(' ') {
+            self.output.pop();
+        }
+        self.output.push('\n');
+        self.at_line_start = true;
+    }
+}
@@ -1,10 +1,16 @@
 // pub type Object = String;
 use std; 
+use std::rc::Rc;
+
+use super::lox_types::{LoxBigInt, LoxDecimal};
 
 #[derive(Debug,PartialEq,Clone)]
 pub enum Literal {
     String(String),
+    Int(i64),
     Num(f64),
+    BigInt(Rc<LoxBigInt>),
+    Decimal(Rc<LoxDecimal>),
     Bool(bool),
     Nil
 }
@@ -13,7 +19,10 @@ impl std::fmt::Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
             Literal::String(ref string) => write!(f, "{}", string),
+            Literal::Int(ref number) => write!(f, "{}", number),
             Literal::Num(ref number) => write!(f, "{}", number),
+            Literal::BigInt(ref number) => write!(f, "{}n", number),
+            Literal::Decimal(ref number) => write!(f, "{}d", number),
             Literal::Bool(ref b) => write!(f, "{}", b),
             Literal::Nil => write!(f, "nil"),
         }
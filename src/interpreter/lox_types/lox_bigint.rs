@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+
+/// Each limb holds nine decimal digits, which keeps parsing and printing cheap.
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An arbitrary-precision signed integer.
+/// The magnitude is stored little-endian in base 10^9 with no leading zero limbs,
+/// so zero is an empty magnitude and is never negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoxBigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl LoxBigInt {
+    pub fn zero() -> LoxBigInt {
+        LoxBigInt {
+            negative: false,
+            magnitude: Vec::new(),
+        }
+    }
+
+    pub fn from_i64(n: i64) -> LoxBigInt {
+        let mut rest = n.unsigned_abs();
+        let mut magnitude = Vec::new();
+        while rest > 0 {
+            magnitude.push((rest % BASE) as u32);
+            rest /= BASE;
+        }
+        LoxBigInt::new(n < 0, magnitude)
+    }
+
+    /// Parses an optionally signed string of decimal digits.
+    pub fn parse(string: &str) -> Option<LoxBigInt> {
+        let (negative, digits) = match string.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, string.strip_prefix('+').unwrap_or(string)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut magnitude = Vec::with_capacity(digits.len() / BASE_DIGITS + 1);
+        let mut end = digits.len();
+        while end > 0 {
+            let start = end.saturating_sub(BASE_DIGITS);
+            magnitude.push(digits[start..end].parse::<u32>().ok()?);
+            end = start;
+        }
+        Some(LoxBigInt::new(negative, magnitude))
+    }
+
+    /// Returns 10 raised to `exponent`.
+    pub fn pow10(exponent: u32) -> LoxBigInt {
+        let exponent = exponent as usize;
+        let mut magnitude = vec![0; exponent / BASE_DIGITS];
+        magnitude.push(10u32.pow((exponent % BASE_DIGITS) as u32));
+        LoxBigInt::new(false, magnitude)
+    }
+
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> LoxBigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        LoxBigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.magnitude.first().is_none_or(|limb| limb % 2 == 0)
+    }
+
+    pub fn abs(&self) -> LoxBigInt {
+        LoxBigInt::new(false, self.magnitude.clone())
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut value: i128 = 0;
+        for limb in self.magnitude.iter().rev() {
+            value = value * BASE as i128 + *limb as i128;
+            if value > i64::MAX as i128 + 1 {
+                return None;
+            }
+        }
+        if self.negative {
+            value = -value;
+        }
+        i64::try_from(value).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse::<f64>().unwrap()
+    }
+
+    /// Truncating division returning the quotient and remainder,
+    /// or None when dividing by zero.  The remainder takes the sign of `self`.
+    pub fn div_rem(&self, rhs: &LoxBigInt) -> Option<(LoxBigInt, LoxBigInt)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &rhs.magnitude);
+        Some((
+            LoxBigInt::new(self.negative != rhs.negative, quotient),
+            LoxBigInt::new(self.negative, remainder),
+        ))
+    }
+}
+
+fn compare_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0;
+    for i in 0..left.len().max(right.len()) {
+        let sum = *left.get(i).unwrap_or(&0) as u64 + *right.get(i).unwrap_or(&0) as u64 + carry;
+        result.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+/// Subtracts `right` from `left`, where `left` must be the larger magnitude.
+fn sub_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(left.len());
+    let mut borrow = 0;
+    for (i, limb) in left.iter().enumerate() {
+        let subtrahend = *right.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut difference = *limb as i64 - subtrahend;
+        borrow = 0;
+        if difference < 0 {
+            difference += BASE as i64;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result
+}
+
+fn mul_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    if left.is_empty() || right.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u64; left.len() + right.len()];
+    for (i, l) in left.iter().enumerate() {
+        let mut carry = 0;
+        for (j, r) in right.iter().enumerate() {
+            let product = result[i + j] + *l as u64 * *r as u64 + carry;
+            result[i + j] = product % BASE;
+            carry = product / BASE;
+        }
+        result[i + right.len()] += carry;
+    }
+    let mut result: Vec<u32> = result.into_iter().map(|limb| limb as u32).collect();
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result
+}
+
+fn mul_small(left: &[u32], right: u32) -> Vec<u32> {
+    mul_magnitude(left, &[right])
+}
+
+/// Schoolbook long division, finding each quotient limb by binary search.
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0; dividend.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+
+    for i in (0..dividend.len()).rev() {
+        remainder.insert(0, dividend[i]);
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+
+        let (mut low, mut high) = (0u64, BASE - 1);
+        while low < high {
+            let middle = (low + high).div_ceil(2);
+            if compare_magnitude(&mul_small(divisor, middle as u32), &remainder) == Ordering::Greater {
+                high = middle - 1;
+            } else {
+                low = middle;
+            }
+        }
+
+        if low > 0 {
+            remainder = sub_magnitude(&remainder, &mul_small(divisor, low as u32));
+        }
+        quotient[i] = low as u32;
+    }
+
+    while quotient.last() == Some(&0) {
+        quotient.pop();
+    }
+    (quotient, remainder)
+}
+
+impl std::ops::Neg for &LoxBigInt {
+    type Output = LoxBigInt;
+
+    fn neg(self) -> LoxBigInt {
+        LoxBigInt::new(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl std::ops::Add for &LoxBigInt {
+    type Output = LoxBigInt;
+
+    fn add(self, rhs: &LoxBigInt) -> LoxBigInt {
+        if self.negative == rhs.negative {
+            return LoxBigInt::new(self.negative, add_magnitude(&self.magnitude, &rhs.magnitude));
+        }
+        match compare_magnitude(&self.magnitude, &rhs.magnitude) {
+            Ordering::Less => LoxBigInt::new(rhs.negative, sub_magnitude(&rhs.magnitude, &self.magnitude)),
+            _ => LoxBigInt::new(self.negative, sub_magnitude(&self.magnitude, &rhs.magnitude)),
+        }
+    }
+}
+
+impl std::ops::Sub for &LoxBigInt {
+    type Output = LoxBigInt;
+
+    fn sub(self, rhs: &LoxBigInt) -> LoxBigInt {
+        self + &(-rhs)
+    }
+}
+
+impl std::ops::Mul for &LoxBigInt {
+    type Output = LoxBigInt;
+
+    fn mul(self, rhs: &LoxBigInt) -> LoxBigInt {
+        LoxBigInt::new(self.negative != rhs.negative, mul_magnitude(&self.magnitude, &rhs.magnitude))
+    }
+}
+
+impl Ord for LoxBigInt {
+    fn cmp(&self, other: &LoxBigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for LoxBigInt {
+    fn partial_cmp(&self, other: &LoxBigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for LoxBigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut limbs = self.magnitude.iter().rev();
+        match limbs.next() {
+            None => write!(f, "0"),
+            Some(first) => {
+                if self.negative {
+                    write!(f, "-")?;
+                }
+                write!(f, "{}", first)?;
+                for limb in limbs {
+                    write!(f, "{:09}", limb)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
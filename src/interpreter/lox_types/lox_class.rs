@@ -7,8 +7,10 @@ use super::super::{
     LoxType,
     EvaluationError,
     LoxFunc,
+    Token,
 };
-use super::{Callable,LoxInstance};
+use super::{Arity,Callable,LoxInstance,LoxTrait};
+use super::lox_instance::private_key_owner;
 
 #[derive(Debug)]
 pub struct LoxClass {
@@ -16,16 +18,24 @@ pub struct LoxClass {
 }
 
 impl LoxClass {
-    pub fn new(name: &str, methods: HashMap<String, LoxFunc>, superclass: Option<Rc<LoxClass>>) -> LoxClass {
+    pub fn new(name: &str, methods: HashMap<String, LoxFunc>, superclass: Option<Rc<LoxClass>>, traits: Vec<Rc<LoxTrait>>) -> LoxClass {
         LoxClass {
             internal: Rc::new(LoxClassInternal {
                 name: name.to_string(),
                 methods,
                 superclass,
+                traits,
             }),               
         }
     }
 
+    /// Wraps the class of an existing instance.
+    pub fn from_internal(internal: Rc<LoxClassInternal>) -> LoxClass {
+        LoxClass {
+            internal,
+        }
+    }
+
     pub fn instantiate(&self) -> LoxInstance {
         LoxInstance::new(self.internal.clone())
     }
@@ -33,6 +43,38 @@ impl LoxClass {
     pub fn find_method(&self, name: &str) -> Option<LoxFunc> {
         self.internal.find_method(name)
     }
+
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        self.internal.is_subclass_of(other)
+    }
+
+    pub fn has_trait(&self, trait_type: &LoxTrait) -> bool {
+        self.internal.has_trait(trait_type)
+    }
+
+    pub fn superclass(&self) -> Option<Rc<LoxClass>> {
+        self.internal.superclass.clone()
+    }
+
+    /// Names of the public methods the class defines or inherits, sorted.
+    pub fn method_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.internal.methods
+            .keys()
+            .filter(|name| !name.starts_with('#'))
+            .cloned()
+            .collect();
+        if let Some(ref superclass) = self.internal.superclass {
+            names.extend(superclass.method_names());
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Whether both refer to the same class declaration.
+    pub fn same_class(&self, other: &LoxClass) -> bool {
+        Rc::ptr_eq(&self.internal, &other.internal)
+    }
 }
 
 impl std::fmt::Display for LoxClass {
@@ -43,25 +85,36 @@ impl std::fmt::Display for LoxClass {
 
 impl Callable for LoxClass {
     fn call(&self, interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType,EvaluationError> {
+        self.call_with_named(interpreter, arguments, &[])
+    }
+
+    fn arity(&self) -> Arity {
+        match self.internal.find_method("init") {
+            Some(initializer) => initializer.arity(),
+            None => Arity::exact(0),
+        }
+    }
+
+    fn call_with_named(&self, interpreter: &mut Interpreter, arguments: &[LoxType], named: &[(Token, LoxType)]) -> Result<LoxType,EvaluationError> {
         let instance = Rc::new(
             RefCell::new(
                 LoxInstance::new(self.internal.clone())
             )
         );
         
-        if let Some(initializer) = self.find_method("init") {
-            initializer.bind(instance.clone()).call(interpreter, arguments)?;
+        match self.find_method("init") {
+            Some(initializer) => {
+                initializer.bind(instance.clone()).call_with_named(interpreter, arguments, named)?;
+            },
+            None if !named.is_empty() => return Err(EvaluationError::ArgumentError(
+                Some(named[0].0.clone()),
+                format!("{} has no initializer to take named arguments", self)
+            )),
+            None => ()
         }
 
         Ok(LoxType::Instance(instance))
     }
-
-    fn arity(&self) -> usize {
-        match self.internal.find_method("init") {
-            Some(initializer) => initializer.arity(),
-            None => 0,
-        }
-    }
 }
 
 #[derive(Debug)]
@@ -69,6 +122,7 @@ pub struct LoxClassInternal {
     pub name: String,
     pub methods: HashMap<String, LoxFunc>,
     pub superclass: Option<Rc<LoxClass>>,
+    pub traits: Vec<Rc<LoxTrait>>,
 }
 
 impl std::fmt::Display for LoxClassInternal {
@@ -78,6 +132,14 @@ impl std::fmt::Display for LoxClassInternal {
 }
 
 impl LoxClassInternal {
+    /// The id of the class, in this one's inheritance chain, with a private method by this name.
+    pub fn private_method_owner(&self, name: &str) -> Option<i64> {
+        self.methods
+            .keys()
+            .find_map(|key| private_key_owner(key, name))
+            .or_else(|| self.superclass.as_ref()?.internal.private_method_owner(name))
+    }
+
     pub fn find_method(&self, name: &str) -> Option<LoxFunc> {
         if let Some(found) = self.methods.get(name).cloned() {
             return Some(found);
@@ -89,5 +151,29 @@ impl LoxClassInternal {
 
         None
     }
+
+    /// Whether this class is `other` or inherits from it.
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        if std::ptr::eq(self, &*other.internal) {
+            return true;
+        }
+
+        match self.superclass {
+            Some(ref superclass) => superclass.is_subclass_of(other),
+            None => false
+        }
+    }
+
+    /// Whether this class or one of its superclasses uses `trait_type`.
+    pub fn has_trait(&self, trait_type: &LoxTrait) -> bool {
+        if self.traits.iter().any(|used| std::ptr::eq(&**used, trait_type)) {
+            return true;
+        }
+
+        match self.superclass {
+            Some(ref superclass) => superclass.has_trait(trait_type),
+            None => false
+        }
+    }
 }
 
@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+
+use super::LoxBigInt;
+
+/// Minimum number of fractional digits kept when a division does not terminate.
+const DIVISION_SCALE: u32 = 16;
+
+/// A fixed-scale decimal number with the value `unscaled / 10^scale`.
+/// The scale is kept through addition and subtraction so `1.50d` stays `1.50`.
+#[derive(Debug, Clone)]
+pub struct LoxDecimal {
+    unscaled: LoxBigInt,
+    scale: u32,
+}
+
+impl LoxDecimal {
+    pub fn new(unscaled: LoxBigInt, scale: u32) -> LoxDecimal {
+        LoxDecimal {
+            unscaled,
+            scale,
+        }
+    }
+
+    pub fn from_bigint(value: LoxBigInt) -> LoxDecimal {
+        LoxDecimal::new(value, 0)
+    }
+
+    /// Parses an optionally signed decimal string such as `-12.340`.
+    pub fn parse(string: &str) -> Option<LoxDecimal> {
+        let (whole, fraction) = match string.split_once('.') {
+            Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+            Some(_) => return None,
+            None => (string, ""),
+        };
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let unscaled = LoxBigInt::parse(&format!("{}{}", whole, fraction))?;
+        Some(LoxDecimal::new(unscaled, fraction.len() as u32))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.unscaled.is_zero()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse::<f64>().unwrap()
+    }
+
+    /// Returns the unscaled value expressed at a larger scale.
+    fn unscaled_at(&self, scale: u32) -> LoxBigInt {
+        &self.unscaled * &LoxBigInt::pow10(scale - self.scale)
+    }
+
+    fn aligned(&self, other: &LoxDecimal) -> (LoxBigInt, LoxBigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (self.unscaled_at(scale), other.unscaled_at(scale), scale)
+    }
+
+    /// Divides, rounding half to even once the quotient has more fractional
+    /// digits than either operand or `DIVISION_SCALE`.  Returns None on division by zero.
+    pub fn checked_div(&self, rhs: &LoxDecimal) -> Option<LoxDecimal> {
+        let minimum_scale = self.scale.max(rhs.scale);
+        let scale = minimum_scale.max(DIVISION_SCALE);
+
+        let dividend = &self.unscaled * &LoxBigInt::pow10(scale + rhs.scale - self.scale);
+        let (mut quotient, remainder) = dividend.div_rem(&rhs.unscaled)?;
+
+        let twice_remainder = &remainder.abs() * &LoxBigInt::from_i64(2);
+        let round_away = match twice_remainder.cmp(&rhs.unscaled.abs()) {
+            Ordering::Greater => true,
+            Ordering::Equal => !quotient.is_even(),
+            Ordering::Less => false,
+        };
+        if round_away {
+            let step = if dividend.is_negative() != rhs.unscaled.is_negative() { -1 } else { 1 };
+            quotient = &quotient + &LoxBigInt::from_i64(step);
+        }
+
+        let mut result = LoxDecimal::new(quotient, scale);
+        let ten = LoxBigInt::from_i64(10);
+        while result.scale > minimum_scale {
+            match result.unscaled.div_rem(&ten) {
+                Some((shorter, remainder)) if remainder.is_zero() => {
+                    result = LoxDecimal::new(shorter, result.scale - 1);
+                },
+                _ => break,
+            }
+        }
+        Some(result)
+    }
+
+    /// Divides and truncates towards zero, returning a whole decimal.
+    pub fn checked_trunc_div(&self, rhs: &LoxDecimal) -> Option<LoxDecimal> {
+        let (dividend, divisor, _) = self.aligned(rhs);
+        let (quotient, _) = dividend.div_rem(&divisor)?;
+        Some(LoxDecimal::from_bigint(quotient))
+    }
+
+    /// Remainder of truncating division, taking the sign of `self`.
+    pub fn checked_rem(&self, rhs: &LoxDecimal) -> Option<LoxDecimal> {
+        let (dividend, divisor, scale) = self.aligned(rhs);
+        let (_, remainder) = dividend.div_rem(&divisor)?;
+        Some(LoxDecimal::new(remainder, scale))
+    }
+}
+
+impl std::ops::Neg for &LoxDecimal {
+    type Output = LoxDecimal;
+
+    fn neg(self) -> LoxDecimal {
+        LoxDecimal::new(-&self.unscaled, self.scale)
+    }
+}
+
+impl std::ops::Add for &LoxDecimal {
+    type Output = LoxDecimal;
+
+    fn add(self, rhs: &LoxDecimal) -> LoxDecimal {
+        let (left, right, scale) = self.aligned(rhs);
+        LoxDecimal::new(&left + &right, scale)
+    }
+}
+
+impl std::ops::Sub for &LoxDecimal {
+    type Output = LoxDecimal;
+
+    fn sub(self, rhs: &LoxDecimal) -> LoxDecimal {
+        let (left, right, scale) = self.aligned(rhs);
+        LoxDecimal::new(&left - &right, scale)
+    }
+}
+
+impl std::ops::Mul for &LoxDecimal {
+    type Output = LoxDecimal;
+
+    fn mul(self, rhs: &LoxDecimal) -> LoxDecimal {
+        LoxDecimal::new(&self.unscaled * &rhs.unscaled, self.scale + rhs.scale)
+    }
+}
+
+impl PartialEq for LoxDecimal {
+    fn eq(&self, other: &LoxDecimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for LoxDecimal {}
+
+impl Ord for LoxDecimal {
+    fn cmp(&self, other: &LoxDecimal) -> Ordering {
+        let (left, right, _) = self.aligned(other);
+        left.cmp(&right)
+    }
+}
+
+impl PartialOrd for LoxDecimal {
+    fn partial_cmp(&self, other: &LoxDecimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for LoxDecimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.unscaled);
+        }
+
+        let digits = self.unscaled.abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+
+        if self.unscaled.is_negative() {
+            write!(f, "-")?;
+        }
+        write!(f, "{}.{}", whole, fraction)
+    }
+}
@@ -0,0 +1,171 @@
+use std::rc::Rc;
+
+use super::super::{
+    Interpreter,
+    LoxType,
+    EvaluationError,
+    EvaluationResult,
+    Token,
+};
+use super::{Arity, Callable};
+
+/// The name and payload field names of one variant of an enum.
+#[derive(Debug)]
+pub struct VariantDef {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct LoxEnumInternal {
+    pub name: String,
+    pub variants: Vec<VariantDef>,
+}
+
+/// An enum declaration.  Variants without a payload are created once with the
+/// enum, so comparing them by identity compares them by variant.
+#[derive(Debug)]
+pub struct LoxEnum {
+    internal: Rc<LoxEnumInternal>,
+    units: Vec<Option<Rc<LoxVariant>>>,
+}
+
+impl LoxEnum {
+    pub fn new(name: &str, variants: Vec<VariantDef>) -> LoxEnum {
+        let internal = Rc::new(LoxEnumInternal {
+            name: name.to_string(),
+            variants,
+        });
+        let units = internal.variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| match variant.fields.is_empty() {
+                true => Some(Rc::new(LoxVariant::new(internal.clone(), index, Vec::new()))),
+                false => None
+            })
+            .collect();
+
+        LoxEnum {
+            internal,
+            units,
+        }
+    }
+
+    pub fn variant_count(&self) -> usize {
+        self.internal.variants.len()
+    }
+
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.internal.variants.iter().position(|variant| variant.name == name)
+    }
+
+    /// The value of `Enum.Variant`: the variant itself when it has no payload,
+    /// otherwise a function constructing it.
+    pub fn variant(&self, index: usize) -> LoxType {
+        match self.units[index] {
+            Some(ref unit) => LoxType::Variant(unit.clone()),
+            None => LoxType::Func(Rc::new(VariantConstructor {
+                owner: self.internal.clone(),
+                index,
+            })),
+        }
+    }
+
+    /// Whether `variant` is one of this enum's variants.
+    pub fn contains(&self, variant: &LoxVariant) -> bool {
+        Rc::ptr_eq(&self.internal, &variant.owner)
+    }
+
+    /// Whether `variant` is the variant at `index` of this enum.
+    pub fn is_variant(&self, variant: &LoxVariant, index: usize) -> bool {
+        Rc::ptr_eq(&self.internal, &variant.owner) && variant.index == index
+    }
+
+    pub fn get(&self, name: &Token) -> EvaluationResult<LoxType> {
+        self.variant_index(&name.lexeme)
+            .map(|index| self.variant(index))
+            .ok_or_else(|| EvaluationError::UndefinedIdentifierError(name.clone()))
+    }
+}
+
+impl std::fmt::Display for LoxEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.internal.name)
+    }
+}
+
+/// A value of an enum, holding its payload if the variant has fields.
+#[derive(Debug)]
+pub struct LoxVariant {
+    owner: Rc<LoxEnumInternal>,
+    index: usize,
+    values: Vec<LoxType>,
+}
+
+impl LoxVariant {
+    fn new(owner: Rc<LoxEnumInternal>, index: usize, values: Vec<LoxType>) -> LoxVariant {
+        LoxVariant {
+            owner,
+            index,
+            values,
+        }
+    }
+
+    pub fn values(&self) -> &[LoxType] {
+        &self.values
+    }
+
+    pub fn field(&self, name: &str) -> Option<LoxType> {
+        self.owner.variants[self.index].fields
+            .iter()
+            .position(|field| field == name)
+            .map(|position| self.values[position].clone())
+    }
+
+    pub fn get(&self, name: &Token) -> EvaluationResult<LoxType> {
+        self.field(&name.lexeme)
+            .ok_or_else(|| EvaluationError::UndefinedIdentifierError(name.clone()))
+    }
+}
+
+impl std::fmt::Display for LoxVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}", self.owner.name, self.owner.variants[self.index].name)?;
+        if self.values.is_empty() {
+            return Ok(());
+        }
+
+        write!(f, "(")?;
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Creates values of a variant with a payload, taking one argument per field.
+#[derive(Debug)]
+struct VariantConstructor {
+    owner: Rc<LoxEnumInternal>,
+    index: usize,
+}
+
+impl std::fmt::Display for VariantConstructor {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<variant {}.{}>", self.owner.name, self.owner.variants[self.index].name)
+    }
+}
+
+impl Callable for VariantConstructor {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let variant = LoxVariant::new(self.owner.clone(), self.index, arguments.to_vec());
+        Ok(LoxType::Variant(Rc::new(variant)))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(self.owner.variants[self.index].fields.len())
+    }
+}
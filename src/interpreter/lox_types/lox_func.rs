@@ -9,45 +9,54 @@ use super::super::{
     Environment,
     Token,
     LoxInstance,
-    TokenType
+    TokenType,
+    Param,
+    EvaluationResult,
+    at_call_site,
 };
-use super::Callable;
+use super::{Arity, Callable, LoxGenerator, LoxList};
+use super::lox_generator::contains_yield;
 
 #[derive(Debug,Clone)]
 pub struct LoxFunc {
     name: Token,
-    params: Vec<Token>,
-    body: Vec<Stmt>,
+    params: Vec<Param>,
+    // Shared with bound copies of the function and the generators it starts
+    body: Rc<[Stmt]>,
     closure: Rc<RefCell<Environment>>,
     is_initializer: bool,
+    is_generator: bool,
 }
 
 impl LoxFunc {
     pub fn new(name: Token,
-        params: Vec<Token>,
+        params: Vec<Param>,
         body: Vec<Stmt>,
         closure: Rc<RefCell<Environment>>,
         is_initializer: bool
     ) -> LoxFunc {
+        let is_generator = body.iter().any(contains_yield);
         LoxFunc {
             name,
             params,
-            body,
+            body: Rc::from(body),
             closure, 
             is_initializer,               
+            is_generator,
         }
     }
 
+    pub fn name(&self) -> &Token {
+        &self.name
+    }
+
     pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunc {
         let mut environment = Environment::from(self.closure.clone());
         environment.define("this", &LoxType::Instance(instance.clone()));
-        LoxFunc::new(
-            self.name.clone(),
-            self.params.clone(),
-            self.body.clone(),
-            Rc::new(RefCell::new(environment)),
-            self.is_initializer
-        )
+        LoxFunc {
+            closure: Rc::new(RefCell::new(environment)),
+            ..self.clone()
+        }
     }
 }
 
@@ -57,23 +66,116 @@ impl std::fmt::Display for LoxFunc {
     }
 }
 
+impl LoxFunc {
+    /// Creates the environment for a call, matching positional and named arguments
+    /// to parameters and filling the rest from defaults or a rest parameter.
+    fn bind_arguments(&self, interpreter: &mut Interpreter, arguments: &[LoxType], named: &[(Token, LoxType)]) -> EvaluationResult<Rc<RefCell<Environment>>> {
+        for (name, _) in named {
+            if !self.params.iter().any(|param| !param.rest && param.name.lexeme == name.lexeme) {
+                return Err(EvaluationError::ArgumentError(
+                    Some(name.clone()),
+                    format!("{} has no parameter named {}", self, name.lexeme)
+                ));
+            }
+        }
+
+        let environment = Rc::new(RefCell::new(Environment::from(self.closure.clone())));
+        let mut positional = arguments.iter();
+
+        for param in &self.params {
+            let by_name = named.iter().find(|(name, _)| name.lexeme == param.name.lexeme);
+            let value = if param.rest {
+                LoxType::List(Rc::new(LoxList::new(positional.by_ref().cloned().collect())))
+            } else if let Some(value) = positional.next() {
+                if let Some((name, _)) = by_name {
+                    return Err(EvaluationError::ArgumentError(
+                        Some(name.clone()),
+                        format!("{} got more than one value for {}", self, name.lexeme)
+                    ));
+                }
+                value.clone()
+            } else if let Some((_, value)) = by_name {
+                value.clone()
+            } else if let Some(default) = &param.default {
+                let previous = std::mem::replace(&mut interpreter.environment, environment.clone());
+                let value = interpreter.evaluate_expr(default);
+                interpreter.environment = previous;
+                value?
+            } else {
+                return Err(EvaluationError::ArgumentError(
+                    None,
+                    format!("{} is missing a value for {}", self, param.name.lexeme)
+                ));
+            };
+            environment.borrow_mut().define(&param.name.lexeme, &value);
+        }
+
+        if positional.next().is_some() {
+            return Err(EvaluationError::ArgumentError(
+                None,
+                format!("{} takes {} arguments but got {}", self, self.arity(), arguments.len())
+            ));
+        }
+        Ok(environment)
+    }
+}
+
 impl Callable for LoxFunc {
     fn call(&self, interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType,EvaluationError> {
-        // if self.is_initializer {
-        //     return self.closure.borrow().get_at(
-        //         &Token::new(TokenType::This, "this", None, 0),
-        //         0
-        //     );
-        // }
-        
-        let mut environment = Environment::from(self.closure.clone());
+        self.call_with_named(interpreter, arguments, &[])
+    }
 
-        for (i,param) in self.params.iter().enumerate() {
-            let arg = arguments.get(i).unwrap();
-            environment.define(&param.lexeme, arg);
+    fn arity(&self) -> Arity {
+        let required = self.params.iter().filter(|param| !param.rest && param.default.is_none()).count();
+        if self.params.last().is_some_and(|param| param.rest) {
+            Arity::at_least(required)
+        } else {
+            Arity::range(required, self.params.len())
         }
+    }
 
-        match interpreter.execute_block(&self.body, environment)? {
+    fn call_with_named(&self, interpreter: &mut Interpreter, arguments: &[LoxType], named: &[(Token, LoxType)]) -> Result<LoxType,EvaluationError> {
+        let mut result = self.invoke(interpreter, arguments, named)?;
+
+        // Trampoline: calls returned in tail position are made here, after the
+        // function that returned them has unwound, instead of nesting inside it
+        while let Some(tail) = interpreter.pending_tail_call.take() {
+            result = match tail.callee.as_function() {
+                Some(func) => func.invoke(interpreter, &tail.arguments, &tail.named),
+                None => tail.callee.call_with_named(interpreter, &tail.arguments, &tail.named),
+            }.map_err(|e| at_call_site(e, &tail.paren))?;
+        }
+        Ok(result)
+    }
+
+    fn as_function(&self) -> Option<&LoxFunc> {
+        Some(self)
+    }
+}
+
+impl LoxFunc {
+    /// Runs the body once. A call it returns in tail position is left
+    /// pending on the interpreter for `call_with_named` to make.
+    fn invoke(&self, interpreter: &mut Interpreter, arguments: &[LoxType], named: &[(Token, LoxType)]) -> EvaluationResult<LoxType> {
+        let environment = self.bind_arguments(interpreter, arguments, named)?;
+
+        // Calling a generator function only sets up its parameters, the body runs on demand
+        if self.is_generator {
+            let generator = LoxGenerator::new(self.name.clone(), self.body.clone(), environment);
+            return Ok(LoxType::Generator(Rc::new(generator)));
+        }
+
+        if let Some(debugger) = interpreter.debugger.as_mut() {
+            debugger.enter(&self.name.lexeme);
+        }
+        let enclosing = std::mem::replace(&mut interpreter.tail_calls, !self.is_initializer);
+        let result = interpreter.execute_block_in(&self.body, environment);
+        interpreter.tail_calls = enclosing;
+        if let Some(debugger) = interpreter.debugger.as_mut() {
+            debugger.leave();
+        }
+
+        match result? {
             _ if self.is_initializer => self.closure.borrow().get_at(
                 &Token::new(TokenType::This, "this", None, 0),
                 0
@@ -82,8 +184,4 @@ impl Callable for LoxFunc {
             None => Ok(LoxType::Nil)
         }
     }
-
-    fn arity(&self) -> usize {
-        self.params.len()
-    }
 }
@@ -0,0 +1,387 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::{
+    Interpreter,
+    LoxType,
+    LoxIterator,
+    EvaluationError,
+    EvaluationResult,
+    Environment,
+    Stmt,
+    Expr,
+    Token,
+};
+use super::{Arity, Callable};
+
+/// The suspended call of a function whose body contains `yield`.
+/// A tree-walking interpreter can't pause its own Rust call stack, so a generator
+/// keeps an explicit stack of frames for the statements enclosing a `yield` and
+/// hands every other statement to the interpreter as usual.
+#[derive(Debug)]
+pub struct LoxGenerator {
+    name: Token,
+    state: RefCell<GeneratorState>,
+}
+
+#[derive(Debug)]
+struct GeneratorState {
+    // The function's body, shared with the function, which frames point into
+    body: Rc<[Stmt]>,
+    frames: Vec<Frame>,
+    // What to do with the value passed in when the generator resumes
+    resume: Resume,
+}
+
+#[derive(Debug)]
+enum Resume {
+    Discard,
+    Assign(Token, Option<usize>),
+    Define(Token),
+}
+
+/// Where a list of statements sits in a generator's body: for each enclosing
+/// statement, its index in its list and which of its bodies holds the next list.
+/// A body that is a single statement counts as a list of one.
+type Location = Vec<(usize, usize)>;
+
+#[derive(Debug)]
+enum Frame {
+    Block {
+        stmts: Location,
+        index: usize,
+        environment: Rc<RefCell<Environment>>,
+    },
+    // The body of a `while` or `for in` loop, whose statement encloses it
+    While {
+        body: Location,
+        environment: Rc<RefCell<Environment>>,
+    },
+    ForIn {
+        iterator: LoxIterator,
+        body: Location,
+        environment: Rc<RefCell<Environment>>,
+    },
+}
+
+enum Step {
+    Continue,
+    Yield(LoxType),
+    Return,
+}
+
+impl Frame {
+    fn block(stmts: Location, environment: Rc<RefCell<Environment>>) -> Frame {
+        Frame::Block {
+            stmts,
+            index: 0,
+            environment,
+        }
+    }
+
+    fn environment(&self) -> &Rc<RefCell<Environment>> {
+        match self {
+            Frame::Block { environment, .. } |
+            Frame::While { environment, .. } |
+            Frame::ForIn { environment, .. } => environment
+        }
+    }
+}
+
+/// The list of statements at a location.
+fn statements<'a>(body: &'a [Stmt], location: &[(usize, usize)]) -> &'a [Stmt] {
+    location.iter().fold(body, |stmts, &(index, child)| match &stmts[index] {
+        Stmt::Block(stmts) => stmts,
+        Stmt::If(_, then, _) if child == 0 => std::slice::from_ref(then),
+        Stmt::If(_, _, Some(else_stmt)) => std::slice::from_ref(else_stmt),
+        Stmt::While(_, body) | Stmt::ForIn(_, _, _, body) => std::slice::from_ref(body),
+        Stmt::Match(_, _, arms) => std::slice::from_ref(&arms[child].body),
+        stmt => unreachable!("No statements inside {}", stmt)
+    })
+}
+
+/// The statement enclosing the list at a location.
+fn enclosing<'a>(body: &'a [Stmt], location: &[(usize, usize)]) -> &'a Stmt {
+    let ((index, _), parent) = location.split_last().expect("The body has no enclosing statement");
+    &statements(body, parent)[*index]
+}
+
+/// The location of one of the bodies of the statement at `index` in the list at `location`.
+fn child(location: &[(usize, usize)], index: usize, body: usize) -> Location {
+    let mut child = location.to_vec();
+    child.push((index, body));
+    child
+}
+
+impl LoxGenerator {
+    pub fn new(name: Token, body: Rc<[Stmt]>, environment: Rc<RefCell<Environment>>) -> LoxGenerator {
+        LoxGenerator {
+            name,
+            state: RefCell::new(GeneratorState {
+                body,
+                frames: vec![Frame::block(Vec::new(), environment)],
+                resume: Resume::Discard,
+            }),
+        }
+    }
+
+    /// True once the body has returned or raised an error.
+    pub fn is_done(&self) -> bool {
+        self.state
+            .try_borrow()
+            .map(|state| state.frames.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Runs the generator until its next `yield`, which is returned,
+    /// or until the body finishes, which returns None.
+    /// `input` becomes the value of the `yield` the generator was paused at.
+    pub fn resume(&self, interpreter: &mut Interpreter, input: LoxType) -> EvaluationResult<Option<LoxType>> {
+        let mut state = self.state.try_borrow_mut().map_err(|_| EvaluationError::NativeError(
+            None,
+            format!("generator {} is already running", self.name.lexeme)
+        ))?;
+
+        let previous = interpreter.environment.clone();
+        let enclosing = std::mem::replace(&mut interpreter.tail_calls, false);
+        let result = state.run(interpreter, input);
+        interpreter.tail_calls = enclosing;
+        interpreter.environment = previous;
+
+        match result {
+            Ok(Some(value)) => Ok(Some(value)),
+            result => {
+                state.frames.clear();
+                result
+            }
+        }
+    }
+
+    pub fn get(generator: &Rc<LoxGenerator>, name: &Token) -> EvaluationResult<LoxType> {
+        let method = match name.lexeme.as_str() {
+            "next" => GeneratorMethod::Next,
+            "send" => GeneratorMethod::Send,
+            "done" => GeneratorMethod::Done,
+            _ => return Err(EvaluationError::UndefinedIdentifierError(name.clone()))
+        };
+        Ok(LoxType::Func(Rc::new(BoundGeneratorMethod {
+            generator: generator.clone(),
+            method,
+        })))
+    }
+}
+
+impl std::fmt::Display for LoxGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "<generator {}>", self.name.lexeme)
+    }
+}
+
+impl GeneratorState {
+    fn run(&mut self, interpreter: &mut Interpreter, input: LoxType) -> EvaluationResult<Option<LoxType>> {
+        let resume = std::mem::replace(&mut self.resume, Resume::Discard);
+        if let Some(frame) = self.frames.last() {
+            interpreter.environment = frame.environment().clone();
+            match resume {
+                Resume::Discard => (),
+                Resume::Assign(name, Some(distance)) => {
+                    interpreter.environment.borrow_mut().assign_at(&name, input, distance)?;
+                },
+                Resume::Assign(name, None) => {
+                    interpreter.globals.borrow_mut().assign(&name, input)?;
+                },
+                Resume::Define(name) => interpreter.define(&name, &input, false)?,
+            }
+        }
+
+        let body = self.body.clone();
+        while let Some(frame) = self.frames.last_mut() {
+            interpreter.environment = frame.environment().clone();
+
+            let next = match frame {
+                Frame::Block { stmts, index, .. } => {
+                    let location = stmts.clone();
+                    let stmts = statements(&body, &location);
+                    if *index >= stmts.len() {
+                        self.frames.pop();
+                        continue;
+                    }
+                    let current = *index;
+                    *index += 1;
+                    match self.step(interpreter, &stmts[current], &location, current)? {
+                        Step::Continue => continue,
+                        Step::Yield(value) => return Ok(Some(value)),
+                        Step::Return => return Ok(None),
+                    }
+                },
+                Frame::While { body: location, environment } => {
+                    let condition = match enclosing(&body, location) {
+                        Stmt::While(condition, _) => condition,
+                        stmt => unreachable!("{} is not a while loop", stmt)
+                    };
+                    if interpreter.evaluate_expr(condition)?.is_truthy() {
+                        Some(Frame::block(location.clone(), environment.clone()))
+                    } else {
+                        None
+                    }
+                },
+                Frame::ForIn { iterator, body: location, environment } => {
+                    let (keyword, name) = match enclosing(&body, location) {
+                        Stmt::ForIn(keyword, name, _, _) => (keyword, name),
+                        stmt => unreachable!("{} is not a for loop", stmt)
+                    };
+                    match iterator.next(interpreter, keyword)? {
+                        Some(item) => {
+                            let mut child = Environment::from(environment.clone());
+                            child.define(&name.lexeme, &item);
+                            Some(Frame::block(location.clone(), Rc::new(RefCell::new(child))))
+                        },
+                        None => None
+                    }
+                },
+            };
+
+            match next {
+                Some(frame) => self.frames.push(frame),
+                None => {
+                    self.frames.pop();
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Executes one statement of the innermost block, the one at `index` in the
+    /// list at `location`.  Statements that can't yield run to completion; the
+    /// others push a frame for their body.
+    fn step(&mut self, interpreter: &mut Interpreter, stmt: &Stmt, location: &[(usize, usize)], index: usize) -> EvaluationResult<Step> {
+        if !contains_yield(stmt) {
+            return match interpreter.evaluate_stmt(stmt)? {
+                Some(_) => Ok(Step::Return),
+                None => Ok(Step::Continue)
+            };
+        }
+
+        let environment = interpreter.environment.clone();
+        match stmt {
+            Stmt::ExprStmt(Expr::Yield(_, value)) => {
+                return self.suspend(interpreter, value, Resume::Discard);
+            },
+            Stmt::ExprStmt(Expr::Assign(name, value, distance)) => {
+                if let Expr::Yield(_, ref value) = **value {
+                    return self.suspend(interpreter, value, Resume::Assign(name.clone(), *distance));
+                }
+            },
+            Stmt::VarDecl(name, Some(Expr::Yield(_, value))) => {
+                return self.suspend(interpreter, value, Resume::Define(name.clone()));
+            },
+            Stmt::Block(_) => {
+                let child_environment = Environment::from(environment);
+                self.frames.push(Frame::block(child(location, index, 0), Rc::new(RefCell::new(child_environment))));
+            },
+            Stmt::If(condition, _, else_stmt) => {
+                let branch = if interpreter.evaluate_expr(condition)?.is_truthy() {
+                    Some(0)
+                } else {
+                    else_stmt.as_ref().map(|_| 1)
+                };
+                if let Some(branch) = branch {
+                    self.frames.push(Frame::block(child(location, index, branch), environment));
+                }
+            },
+            Stmt::While(_, _) => {
+                self.frames.push(Frame::While {
+                    body: child(location, index, 0),
+                    environment,
+                });
+            },
+            Stmt::ForIn(keyword, _, iterable, _) => {
+                let iterable = interpreter.evaluate_expr(iterable)?;
+                let iterator = LoxIterator::new(interpreter, iterable, keyword)?;
+                self.frames.push(Frame::ForIn {
+                    iterator,
+                    body: child(location, index, 0),
+                    environment,
+                });
+            },
+            Stmt::Match(_, subject, arms) => {
+                let subject = interpreter.evaluate_expr(subject)?;
+                if let Some((arm, environment)) = interpreter.select_match_arm(&subject, arms)? {
+                    let arm = arms.iter().position(|other| std::ptr::eq(other, arm)).unwrap();
+                    self.frames.push(Frame::block(child(location, index, arm), environment));
+                }
+            },
+            _ => return Err(EvaluationError::IllegalStatementType(Box::new(stmt.clone())))
+        }
+        Ok(Step::Continue)
+    }
+
+    fn suspend(&mut self, interpreter: &mut Interpreter, value: &Option<Box<Expr>>, resume: Resume) -> EvaluationResult<Step> {
+        let value = match value {
+            Some(value) => interpreter.evaluate_expr(value)?,
+            None => LoxType::Nil
+        };
+        self.resume = resume;
+        Ok(Step::Yield(value))
+    }
+}
+
+/// Whether a statement can suspend a generator.  Nested functions and
+/// classes are not searched since their yields belong to their own calls.
+pub fn contains_yield(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::ExprStmt(Expr::Yield(_, _)) => true,
+        Stmt::ExprStmt(Expr::Assign(_, value, _)) => matches!(**value, Expr::Yield(_, _)),
+        Stmt::VarDecl(_, Some(Expr::Yield(_, _))) => true,
+        Stmt::Block(stmts) => stmts.iter().any(contains_yield),
+        Stmt::If(_, then, else_stmt) => {
+            contains_yield(then) || else_stmt.as_deref().is_some_and(contains_yield)
+        },
+        Stmt::While(_, body) | Stmt::ForIn(_, _, _, body) => contains_yield(body),
+        Stmt::Match(_, _, arms) => arms.iter().any(|arm| contains_yield(&arm.body)),
+        _ => false
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GeneratorMethod {
+    Next,
+    Send,
+    Done,
+}
+
+/// One of a generator's methods, bound to the generator it was read from.
+#[derive(Debug)]
+struct BoundGeneratorMethod {
+    generator: Rc<LoxGenerator>,
+    method: GeneratorMethod,
+}
+
+impl std::fmt::Display for BoundGeneratorMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self.method {
+            GeneratorMethod::Next => "next",
+            GeneratorMethod::Send => "send",
+            GeneratorMethod::Done => "done",
+        };
+        write!(f, "<native_fn {}>", name)
+    }
+}
+
+impl Callable for BoundGeneratorMethod {
+    fn call(&self, interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let input = match self.method {
+            GeneratorMethod::Done => return Ok(LoxType::Bool(self.generator.is_done())),
+            GeneratorMethod::Next => LoxType::Nil,
+            GeneratorMethod::Send => arguments[0].clone(),
+        };
+        Ok(self.generator.resume(interpreter, input)?.unwrap_or(LoxType::Nil))
+    }
+
+    fn arity(&self) -> Arity {
+        match self.method {
+            GeneratorMethod::Send => Arity::exact(1),
+            _ => Arity::exact(0)
+        }
+    }
+}
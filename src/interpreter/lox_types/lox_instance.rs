@@ -7,7 +7,7 @@ use super::super::{
     EvaluationError,
     Token,
 };
-use super::{LoxClassInternal};
+use super::{LoxClass,LoxClassInternal,LoxFunc,LoxTrait};
 
 #[derive(Debug, Clone)]
 pub struct LoxInstance {
@@ -23,21 +23,72 @@ impl LoxInstance {
         }
     }
 
-    pub fn get(&self, name: &Token) -> Result<LoxType, EvaluationError> {
-        if let Some(val) = self.fields.get(&name.lexeme).cloned() {
+    /// Looks up a field, or a method bound to `instance` itself so the method
+    /// can modify the instance through `this`.
+    pub fn get(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Result<LoxType, EvaluationError> {
+        LoxInstance::get_key(instance, &name.lexeme, name)
+    }
+
+    /// Looks up a member stored under `key`, which differs from the name
+    /// for private members.
+    pub fn get_key(instance: &Rc<RefCell<LoxInstance>>, key: &str, name: &Token) -> Result<LoxType, EvaluationError> {
+        if let Some(val) = instance.borrow().fields.get(key).cloned() {
             return Ok(val);
         }
 
-        if let Some(method) = self.class.find_method(&name.lexeme) {
-            let method = method.bind(Rc::new(RefCell::new(self.clone())));
+        let method = instance.borrow().class.find_method(key);
+        if let Some(method) = method {
+            let method = method.bind(instance.clone());
             return Ok(LoxType::Func(Rc::new(method)));
         }
         
         Err(EvaluationError::UndefinedIdentifierError(name.clone()))
     }
 
-    pub fn set(&mut self, name: &Token, value: &LoxType) {
-        self.fields.insert(name.lexeme.clone(), value.clone());
+    /// Whether a field or a method of the instance is private to some class under
+    /// this name, and the id of that class.
+    pub fn private_owner(&self, name: &str) -> Option<(&'static str, i64)> {
+        let field = self.fields.keys().find_map(|key| private_key_owner(key, name));
+        match field {
+            Some(class_id) => Some(("field", class_id)),
+            None => self.class.private_method_owner(name).map(|class_id| ("method", class_id)),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<LoxType> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn set_field(&mut self, name: &str, value: LoxType) {
+        self.fields.insert(name.to_string(), value);
+    }
+
+    /// Names of the instance's public fields, sorted.
+    pub fn field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.fields
+            .keys()
+            .filter(|name| !name.starts_with('#'))
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn class(&self) -> LoxClass {
+        LoxClass::from_internal(self.class.clone())
+    }
+
+    /// Looks up a method of the instance's class without binding it.
+    pub fn find_method(&self, name: &str) -> Option<LoxFunc> {
+        self.class.find_method(name)
+    }
+
+    pub fn is_instance_of(&self, class: &LoxClass) -> bool {
+        self.class.is_subclass_of(class)
+    }
+
+    pub fn has_trait(&self, trait_type: &LoxTrait) -> bool {
+        self.class.has_trait(trait_type)
     }
 }
 
@@ -56,4 +107,9 @@ impl std::fmt::Display for LoxInstance {
 //     fn arity(&self) -> usize {
 //         0
 //     }
-// }
\ No newline at end of file
+// }
+
+/// The class id in the key a private member is stored under, if it's the key for `name`.
+pub fn private_key_owner(key: &str, name: &str) -> Option<i64> {
+    key.strip_prefix(name)?.strip_prefix('@')?.parse().ok()
+}
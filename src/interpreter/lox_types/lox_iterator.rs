@@ -0,0 +1,125 @@
+use std::rc::Rc;
+
+use super::super::{
+    Interpreter,
+    LoxType,
+    LoxInstance,
+    LoxGenerator,
+    LoxList,
+    LoxEnum,
+    EvaluationError,
+    EvaluationResult,
+    Token,
+    TokenType,
+};
+
+/// The state of a for-in loop over a value.
+/// Instances take part through the iterator protocol: an `iter()` method returning
+/// an iterator, which has a `next()` method and a `done` field or method.
+/// Generators are iterated by resuming them until they return, and enums over their variants.
+#[derive(Debug)]
+pub enum LoxIterator {
+    Chars(Vec<char>, usize),
+    Range(i64, i64, i64),
+    Protocol(LoxType),
+    Generator(Rc<LoxGenerator>),
+    List(Rc<LoxList>, usize),
+    Enum(Rc<LoxEnum>, usize),
+}
+
+impl LoxIterator {
+    pub fn new(interpreter: &mut Interpreter, value: LoxType, token: &Token) -> EvaluationResult<LoxIterator> {
+        match value {
+            LoxType::String(ref string) => Ok(LoxIterator::Chars(string.chars().collect(), 0)),
+            LoxType::Range(range) => {
+                let (start, end, step) = *range;
+                Ok(LoxIterator::Range(start, end, step))
+            },
+            LoxType::Generator(generator) => Ok(LoxIterator::Generator(generator)),
+            LoxType::List(list) => Ok(LoxIterator::List(list, 0)),
+            LoxType::Enum(enum_type) => Ok(LoxIterator::Enum(enum_type, 0)),
+            LoxType::Instance(ref instance) => {
+                let iter = LoxInstance::get(instance, &protocol_token("iter", token));
+                match iter {
+                    Ok(iter) => Ok(LoxIterator::Protocol(call_method(interpreter, iter, token)?)),
+                    // Instances without iter() are treated as iterators themselves
+                    Err(_) => Ok(LoxIterator::Protocol(value.clone()))
+                }
+            },
+            _ => Err(EvaluationError::NotIterable(token.clone()))
+        }
+    }
+
+    /// Advances the iterator, returning None once it is exhausted.
+    pub fn next(&mut self, interpreter: &mut Interpreter, token: &Token) -> EvaluationResult<Option<LoxType>> {
+        match self {
+            LoxIterator::Chars(chars, index) => {
+                let next = chars.get(*index).map(|c| LoxType::String(c.to_string()));
+                *index += 1;
+                Ok(next)
+            },
+            LoxIterator::List(list, index) => {
+                let next = list.at(*index);
+                *index += 1;
+                Ok(next)
+            },
+            LoxIterator::Enum(enum_type, index) => {
+                if *index >= enum_type.variant_count() {
+                    return Ok(None);
+                }
+                *index += 1;
+                Ok(Some(enum_type.variant(*index - 1)))
+            },
+            LoxIterator::Range(start, end, step) => {
+                let exhausted = if *step > 0 { *start >= *end } else { *start <= *end };
+                if exhausted {
+                    return Ok(None);
+                }
+                let next = *start;
+                *start = start.saturating_add(*step);
+                Ok(Some(LoxType::Int(next)))
+            },
+            LoxIterator::Protocol(iterator) => {
+                let instance = match iterator {
+                    LoxType::Instance(instance) => instance.clone(),
+                    _ => return Err(EvaluationError::NotIterable(token.clone()))
+                };
+
+                let done = LoxInstance::get(&instance, &protocol_token("done", token))
+                    .map_err(|_| EvaluationError::NotIterable(token.clone()))?;
+                let done = match done {
+                    LoxType::Func(_) => call_method(interpreter, done, token)?,
+                    done => done
+                };
+                if done.is_truthy() {
+                    return Ok(None);
+                }
+
+                let next = LoxInstance::get(&instance, &protocol_token("next", token))
+                    .map_err(|_| EvaluationError::NotIterable(token.clone()))?;
+                Ok(Some(call_method(interpreter, next, token)?))
+            },
+            LoxIterator::Generator(generator) => {
+                generator.resume(interpreter, LoxType::Nil).map_err(|e| match e {
+                    EvaluationError::NativeError(None, message) =>
+                        EvaluationError::NativeError(Some(token.clone()), message),
+                    e => e
+                })
+            }
+        }
+    }
+}
+
+fn protocol_token(name: &str, token: &Token) -> Token {
+    Token::new(TokenType::Identifier, name, None, token.line)
+}
+
+fn call_method(interpreter: &mut Interpreter, method: LoxType, token: &Token) -> EvaluationResult<LoxType> {
+    let callable = method
+        .get_callable()
+        .ok_or_else(|| EvaluationError::CallOnNonCallable(token.clone()))?;
+    if !callable.arity().accepts(0) {
+        return Err(EvaluationError::WrongArity(token.clone(), 0, callable.arity()));
+    }
+    callable.call(interpreter, &[])
+}
@@ -0,0 +1,138 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::super::{
+    Interpreter,
+    LoxType,
+    EvaluationError,
+    EvaluationResult,
+    Token,
+};
+use super::{Arity, Callable};
+
+/// A growable list of values, shared by reference like instances.
+#[derive(Debug)]
+pub struct LoxList {
+    items: RefCell<Vec<LoxType>>,
+}
+
+impl LoxList {
+    pub fn new(items: Vec<LoxType>) -> LoxList {
+        LoxList {
+            items: RefCell::new(items),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.borrow().is_empty()
+    }
+
+    pub fn at(&self, index: usize) -> Option<LoxType> {
+        self.items.borrow().get(index).cloned()
+    }
+
+    pub fn push(&self, value: LoxType) {
+        self.items.borrow_mut().push(value);
+    }
+
+    fn write_items(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (i, item) in self.items.borrow().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        write!(f, "]")
+    }
+
+    pub fn get(list: &Rc<LoxList>, name: &Token) -> EvaluationResult<LoxType> {
+        let method = match name.lexeme.as_str() {
+            "len" => ListMethod::Len,
+            "get" => ListMethod::Get,
+            "push" => ListMethod::Push,
+            _ => return Err(EvaluationError::UndefinedIdentifierError(name.clone()))
+        };
+        Ok(LoxType::Func(Rc::new(BoundListMethod {
+            list: list.clone(),
+            method,
+        })))
+    }
+}
+
+thread_local! {
+    // The lists being formatted, so a list that contains itself shows `[...]` there
+    static FORMATTING: RefCell<Vec<*const LoxList>> = const { RefCell::new(Vec::new()) };
+}
+
+impl std::fmt::Display for LoxList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let list: *const LoxList = self;
+        if FORMATTING.with(|formatting| formatting.borrow().contains(&list)) {
+            return write!(f, "[...]");
+        }
+
+        FORMATTING.with(|formatting| formatting.borrow_mut().push(list));
+        let result = self.write_items(f);
+        FORMATTING.with(|formatting| formatting.borrow_mut().pop());
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ListMethod {
+    Len,
+    Get,
+    Push,
+}
+
+/// One of a list's methods, bound to the list it was read from.
+#[derive(Debug)]
+struct BoundListMethod {
+    list: Rc<LoxList>,
+    method: ListMethod,
+}
+
+impl std::fmt::Display for BoundListMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self.method {
+            ListMethod::Len => "len",
+            ListMethod::Get => "get",
+            ListMethod::Push => "push",
+        };
+        write!(f, "<native_fn {}>", name)
+    }
+}
+
+impl Callable for BoundListMethod {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match self.method {
+            ListMethod::Len => Ok(LoxType::Int(self.list.len() as i64)),
+            ListMethod::Get => {
+                let item = match arguments[0] {
+                    LoxType::Int(index) if index >= 0 => self.list.at(index as usize),
+                    _ => None
+                };
+                item.ok_or_else(|| EvaluationError::NativeError(
+                    None,
+                    format!("List index {} is out of range for a list of length {}", arguments[0], self.list.len())
+                ))
+            },
+            ListMethod::Push => {
+                self.list.push(arguments[0].clone());
+                Ok(LoxType::Nil)
+            },
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        match self.method {
+            ListMethod::Len => Arity::exact(0),
+            _ => Arity::exact(1)
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use super::super::LoxFunc;
+
+/// A named set of methods that classes mix in with `with`.
+/// The methods are copied into each class using the trait, so they bind `this`
+/// like the class's own methods but can't use `super`.
+#[derive(Debug)]
+pub struct LoxTrait {
+    pub name: String,
+    pub methods: HashMap<String, LoxFunc>,
+}
+
+impl LoxTrait {
+    pub fn new(name: &str, methods: HashMap<String, LoxFunc>) -> LoxTrait {
+        LoxTrait {
+            name: name.to_string(),
+            methods,
+        }
+    }
+}
+
+impl std::fmt::Display for LoxTrait {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -1,6 +1,6 @@
 use super::super::{
     token::TokenType,
-    lox_types::{Callable,LoxClass,LoxInstance},
+    lox_types::{Callable,LoxClass,LoxInstance,LoxBigInt,LoxDecimal,LoxGenerator,LoxList,LoxTrait,LoxEnum,LoxVariant},
 };
 
 use std::{cmp::Ordering, rc::Rc, cell::RefCell};
@@ -9,6 +9,8 @@ use std::{cmp::Ordering, rc::Rc, cell::RefCell};
 pub enum LoxTypeError {
     IllegalOperationError,
     IllegalComparisonError(LoxType, LoxType),
+    IntegerOverflow,
+    DivisionByZero,
 }
 
 impl std::fmt::Display for LoxTypeError {
@@ -20,6 +22,12 @@ impl std::fmt::Display for LoxTypeError {
             LoxTypeError::IllegalComparisonError(left, right) => {
                 write!(f,"IllegalComparisonError: between {:?} and {:?}", left, right)
             }
+            LoxTypeError::IntegerOverflow => {
+                write!(f,"IntegerOverflow")
+            }
+            LoxTypeError::DivisionByZero => {
+                write!(f,"DivisionByZero")
+            }
         }
     }
 }
@@ -29,6 +37,8 @@ impl std::error::Error for LoxTypeError {
         match *self {
             LoxTypeError::IllegalOperationError => "IllegalOperationError",
             LoxTypeError::IllegalComparisonError(_,_) => "IllegalComparisonError",
+            LoxTypeError::IntegerOverflow => "IntegerOverflow",
+            LoxTypeError::DivisionByZero => "DivisionByZero",
         }
     }
 }
@@ -37,11 +47,34 @@ impl std::error::Error for LoxTypeError {
 pub enum LoxType {
     Nil,
     Bool(bool),
+    Int(i64),
     Num(f64),
+    // Boxed so values stay small, being copied around the interpreter's frames
+    BigInt(Rc<LoxBigInt>),
+    Decimal(Rc<LoxDecimal>),
     String(String),
+    // Start, end and step
+    Range(Rc<(i64, i64, i64)>),
     Func(Rc<dyn Callable>),
     Class(Rc<LoxClass>),
-    Instance(Rc<RefCell<LoxInstance>>)
+    Trait(Rc<LoxTrait>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    Generator(Rc<LoxGenerator>),
+    List(Rc<LoxList>),
+    Enum(Rc<LoxEnum>),
+    Variant(Rc<LoxVariant>),
+}
+
+impl From<LoxBigInt> for LoxType {
+    fn from(n: LoxBigInt) -> LoxType {
+        LoxType::BigInt(Rc::new(n))
+    }
+}
+
+impl From<LoxDecimal> for LoxType {
+    fn from(n: LoxDecimal) -> LoxType {
+        LoxType::Decimal(Rc::new(n))
+    }
 }
 
 impl std::fmt::Display for LoxType {
@@ -49,8 +82,15 @@ impl std::fmt::Display for LoxType {
         match self {
             LoxType::Nil => write!(f, "nil"),
             LoxType::Bool(b) => write!(f, "{}", b),
+            LoxType::Int(n) => write!(f, "{}", n),
             LoxType::Num(n) => write!(f, "{}", n),
+            LoxType::BigInt(n) => write!(f, "{}", n),
+            LoxType::Decimal(n) => write!(f, "{}", n),
             LoxType::String(s) => write!(f, "{}", s),
+            LoxType::Range(range) => match **range {
+                (start, end, 1) => write!(f, "range({}, {})", start, end),
+                (start, end, step) => write!(f, "range({}, {}, {})", start, end, step),
+            },
             // LoxType::Func(arguments) => {
             //     write!(f, "(")?;
             //     arguments.iter().fold(Ok(()), |result, arg| {
@@ -61,17 +101,76 @@ impl std::fmt::Display for LoxType {
             // }
             LoxType::Func(callable) => write!(f, "{}", self.get_callable().unwrap()),
             LoxType::Class(class) => write!(f, "<class {}>", class),
-            LoxType::Instance(instance) => write!(f, "<instance {}>", instance.borrow())
+            LoxType::Trait(trait_type) => write!(f, "<trait {}>", trait_type),
+            LoxType::Instance(instance) => write!(f, "<instance {}>", instance.borrow()),
+            LoxType::Generator(generator) => write!(f, "{}", generator),
+            LoxType::List(list) => write!(f, "{}", list),
+            LoxType::Enum(enum_type) => write!(f, "<enum {}>", enum_type),
+            LoxType::Variant(variant) => write!(f, "{}", variant),
         }
     }
 }
 
+/// Operands of a binary arithmetic operation after numeric promotion.
+/// Integers are promoted to big integers, and both to decimals, when mixed.
+/// Integers and big integers are promoted to floats when mixed with a float,
+/// but decimals never mix with floats since that would lose their exactness.
+enum NumericPair {
+    Int(i64, i64),
+    Num(f64, f64),
+    BigInt(LoxBigInt, LoxBigInt),
+    Decimal(LoxDecimal, LoxDecimal),
+}
+
+impl NumericPair {
+    fn from(left: &LoxType, right: &LoxType) -> Option<NumericPair> {
+        match (left, right) {
+            (LoxType::Int(l), LoxType::Int(r)) => Some(NumericPair::Int(*l, *r)),
+            (LoxType::Num(l), LoxType::Num(r)) => Some(NumericPair::Num(*l, *r)),
+            (LoxType::Decimal(_), LoxType::Num(_)) |
+            (LoxType::Num(_), LoxType::Decimal(_)) => None,
+            (LoxType::Num(l), r) => Some(NumericPair::Num(*l, r.to_f64()?)),
+            (l, LoxType::Num(r)) => Some(NumericPair::Num(l.to_f64()?, *r)),
+            (LoxType::Decimal(_), _) | (_, LoxType::Decimal(_)) => {
+                Some(NumericPair::Decimal(left.to_decimal()?, right.to_decimal()?))
+            },
+            _ => Some(NumericPair::BigInt(left.to_bigint()?, right.to_bigint()?)),
+        }
+    }
+}
+
+/// Compares an integer against a float without rounding the integer.
+fn compare_int_num(int: i64, num: f64) -> Option<Ordering> {
+    if num.is_nan() {
+        None
+    } else if num >= 9223372036854775808.0 {
+        Some(Ordering::Less)
+    } else if num < -9223372036854775808.0 {
+        Some(Ordering::Greater)
+    } else {
+        let truncated = num.trunc();
+        match int.cmp(&(truncated as i64)) {
+            Ordering::Equal => 0.0.partial_cmp(&(num - truncated)),
+            ordering => Some(ordering)
+        }
+    }
+}
+
+fn checked(result: Option<i64>) -> Result<LoxType, LoxTypeError> {
+    result
+        .map(LoxType::Int)
+        .ok_or(LoxTypeError::IntegerOverflow)
+}
+
 impl std::ops::Neg for LoxType {
     type Output = Result<Self, LoxTypeError>;
 
     fn neg(self) -> Self::Output {
         match self {
+            LoxType::Int(n) => checked(n.checked_neg()),
             LoxType::Num(n) => Ok(LoxType::Num(-n)),
+            LoxType::BigInt(n) => Ok(LoxType::from(-&*n)),
+            LoxType::Decimal(n) => Ok(LoxType::from(-&*n)),
             _ => Err(LoxTypeError::IllegalOperationError)
         }
     }
@@ -92,8 +191,11 @@ impl std::ops::Sub for LoxType {
     type Output = Result<Self, LoxTypeError>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self,rhs) {
-            (LoxType::Num(n1), LoxType::Num(n2)) => Ok(LoxType::Num(n1 - n2)),
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(n1, n2)) => checked(n1.checked_sub(n2)),
+            Some(NumericPair::Num(n1, n2)) => Ok(LoxType::Num(n1 - n2)),
+            Some(NumericPair::BigInt(n1, n2)) => Ok(LoxType::from(&n1 - &n2)),
+            Some(NumericPair::Decimal(n1, n2)) => Ok(LoxType::from(&n1 - &n2)),
             _ => Err(LoxTypeError::IllegalOperationError)
         }
     }
@@ -103,8 +205,36 @@ impl std::ops::Div for LoxType {
     type Output = Result<Self, LoxTypeError>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match (self,rhs) {
-            (LoxType::Num(n1), LoxType::Num(n2)) => Ok(LoxType::Num(n1 / n2)),
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(n1, n2)) => Ok(LoxType::Num(n1 as f64 / n2 as f64)),
+            Some(NumericPair::Num(n1, n2)) => Ok(LoxType::Num(n1 / n2)),
+            // Big integer division is exact up to the decimal division scale
+            Some(NumericPair::BigInt(n1, n2)) => LoxDecimal::from_bigint(n1)
+                .checked_div(&LoxDecimal::from_bigint(n2))
+                .map(LoxType::from)
+                .ok_or(LoxTypeError::DivisionByZero),
+            Some(NumericPair::Decimal(n1, n2)) => n1.checked_div(&n2)
+                .map(LoxType::from)
+                .ok_or(LoxTypeError::DivisionByZero),
+            _ => Err(LoxTypeError::IllegalOperationError)
+        }
+    }
+}
+
+impl std::ops::Rem for LoxType {
+    type Output = Result<Self, LoxTypeError>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(_, 0)) => Err(LoxTypeError::DivisionByZero),
+            Some(NumericPair::Int(n1, n2)) => checked(n1.checked_rem(n2)),
+            Some(NumericPair::Num(n1, n2)) => Ok(LoxType::Num(n1 % n2)),
+            Some(NumericPair::BigInt(n1, n2)) => n1.div_rem(&n2)
+                .map(|(_, remainder)| LoxType::from(remainder))
+                .ok_or(LoxTypeError::DivisionByZero),
+            Some(NumericPair::Decimal(n1, n2)) => n1.checked_rem(&n2)
+                .map(LoxType::from)
+                .ok_or(LoxTypeError::DivisionByZero),
             _ => Err(LoxTypeError::IllegalOperationError)
         }
     }
@@ -114,8 +244,11 @@ impl std::ops::Mul for LoxType {
     type Output = Result<Self, LoxTypeError>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self,rhs) {
-            (LoxType::Num(n1), LoxType::Num(n2)) => Ok(LoxType::Num(n1 * n2)),
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(n1, n2)) => checked(n1.checked_mul(n2)),
+            Some(NumericPair::Num(n1, n2)) => Ok(LoxType::Num(n1 * n2)),
+            Some(NumericPair::BigInt(n1, n2)) => Ok(LoxType::from(&n1 * &n2)),
+            Some(NumericPair::Decimal(n1, n2)) => Ok(LoxType::from(&n1 * &n2)),
             _ => Err(LoxTypeError::IllegalOperationError)
         }
     }
@@ -125,22 +258,45 @@ impl std::ops::Add for LoxType {
     type Output = Result<Self, LoxTypeError>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        match (self,rhs) {
-            (LoxType::Num(left), LoxType::Num(right)) => Ok(LoxType::Num(left + right)),
-            (LoxType::String(left), LoxType::String(right)) => Ok(LoxType::String(left + &right)),
+        if let (LoxType::String(left), LoxType::String(right)) = (&self, &rhs) {
+            return Ok(LoxType::String(left.clone() + right));
+        }
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(left, right)) => checked(left.checked_add(right)),
+            Some(NumericPair::Num(left, right)) => Ok(LoxType::Num(left + right)),
+            Some(NumericPair::BigInt(left, right)) => Ok(LoxType::from(&left + &right)),
+            Some(NumericPair::Decimal(left, right)) => Ok(LoxType::from(&left + &right)),
             _ => Err(LoxTypeError::IllegalOperationError)
         }
     }
 }
 
+/// Orders two numbers of any representation, or None if either isn't a number
+/// or they can't be compared.
+fn compare_numbers(left: &LoxType, right: &LoxType) -> Option<Ordering> {
+    match (left, right) {
+        (LoxType::Int(l), LoxType::Num(r)) => compare_int_num(*l, *r),
+        (LoxType::Num(l), LoxType::Int(r)) => compare_int_num(*r, *l).map(Ordering::reverse),
+        _ => match NumericPair::from(left, right)? {
+            NumericPair::Int(l, r) => l.partial_cmp(&r),
+            NumericPair::Num(l, r) => l.partial_cmp(&r),
+            NumericPair::BigInt(l, r) => l.partial_cmp(&r),
+            NumericPair::Decimal(l, r) => l.partial_cmp(&r),
+        }
+    }
+}
+
 impl std::cmp::PartialEq for LoxType {
     fn eq(&self, rhs: &LoxType) -> bool {
         match (self, rhs) {
-            (LoxType::Num(left),LoxType::Num(right)) => left == right,
             (LoxType::String(left),LoxType::String(right)) => left == right,
             (LoxType::Bool(left),LoxType::Bool(right)) => left == right,
             (LoxType::Nil,LoxType::Nil) => true,
-            _ => false,
+            (LoxType::Class(left),LoxType::Class(right)) => left.same_class(right),
+            (LoxType::Trait(left),LoxType::Trait(right)) => Rc::ptr_eq(left, right),
+            (LoxType::Enum(left),LoxType::Enum(right)) => Rc::ptr_eq(left, right),
+            (LoxType::Variant(left),LoxType::Variant(right)) => Rc::ptr_eq(left, right),
+            _ => compare_numbers(self, rhs) == Some(Ordering::Equal),
         }
     }
 }
@@ -148,10 +304,9 @@ impl std::cmp::PartialEq for LoxType {
 impl std::cmp::PartialOrd for LoxType {
     fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
         match (self, rhs) {
-            (LoxType::Num(left),LoxType::Num(right)) => left.partial_cmp(right),
             (LoxType::String(left),LoxType::String(right)) => left.partial_cmp(right),
             (LoxType::Bool(left),LoxType::Bool(right)) => left.partial_cmp(right),
-            _ => None
+            _ => compare_numbers(self, rhs)
         }
     }
 }
@@ -186,6 +341,82 @@ impl LoxType {
         }
     }
 
+    fn to_f64(&self) -> Option<f64> {
+        match self {
+            LoxType::Int(n) => Some(*n as f64),
+            LoxType::Num(n) => Some(*n),
+            LoxType::BigInt(n) => Some(n.to_f64()),
+            _ => None
+        }
+    }
+
+    fn to_bigint(&self) -> Option<LoxBigInt> {
+        match self {
+            LoxType::Int(n) => Some(LoxBigInt::from_i64(*n)),
+            LoxType::BigInt(n) => Some(LoxBigInt::clone(n)),
+            _ => None
+        }
+    }
+
+    fn to_decimal(&self) -> Option<LoxDecimal> {
+        match self {
+            LoxType::Decimal(n) => Some(LoxDecimal::clone(n)),
+            _ => self.to_bigint().map(LoxDecimal::from_bigint)
+        }
+    }
+
+    /// Truncating division that keeps integer operands as integers.
+    pub fn int_div(self, rhs: LoxType) -> Result<LoxType, LoxTypeError> {
+        match NumericPair::from(&self, &rhs) {
+            Some(NumericPair::Int(_, 0)) => Err(LoxTypeError::DivisionByZero),
+            Some(NumericPair::Int(n1, n2)) => checked(n1.checked_div(n2)),
+            Some(NumericPair::Num(n1, n2)) => Ok(LoxType::Num((n1 / n2).trunc())),
+            Some(NumericPair::BigInt(n1, n2)) => n1.div_rem(&n2)
+                .map(|(quotient, _)| LoxType::from(quotient))
+                .ok_or(LoxTypeError::DivisionByZero),
+            Some(NumericPair::Decimal(n1, n2)) => n1.checked_trunc_div(&n2)
+                .map(LoxType::from)
+                .ok_or(LoxTypeError::DivisionByZero),
+            _ => Err(LoxTypeError::IllegalOperationError)
+        }
+    }
+
+    /// The name `type()` reports for the value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LoxType::Nil => "nil",
+            LoxType::Bool(_) => "bool",
+            LoxType::Int(_) => "int",
+            LoxType::Num(_) => "float",
+            LoxType::BigInt(_) => "bigint",
+            LoxType::Decimal(_) => "decimal",
+            LoxType::String(_) => "string",
+            LoxType::Range(_) => "range",
+            LoxType::Func(_) => "function",
+            LoxType::Class(_) => "class",
+            LoxType::Trait(_) => "trait",
+            LoxType::Instance(_) => "instance",
+            LoxType::Generator(_) => "generator",
+            LoxType::List(_) => "list",
+            LoxType::Enum(_) => "enum",
+            LoxType::Variant(_) => "variant",
+        }
+    }
+
+    /// Evaluates `value is type`: whether an instance belongs to a class or
+    /// one of its subclasses, or uses a trait, or a variant belongs to an enum.
+    /// Returns None when `type` is not a class, trait or enum.
+    pub fn is_a(&self, type_value: &LoxType) -> Option<bool> {
+        let result = match (self, type_value) {
+            (LoxType::Instance(instance), LoxType::Class(class)) => instance.borrow().is_instance_of(class),
+            (LoxType::Instance(instance), LoxType::Trait(trait_type)) => instance.borrow().has_trait(trait_type),
+            (LoxType::Variant(variant), LoxType::Enum(enum_type)) => enum_type.contains(variant),
+            (_, LoxType::Class(_)) | (_, LoxType::Trait(_)) | (_, LoxType::Enum(_)) => false,
+            _ => return None
+        };
+        Some(result)
+    }
+
     pub fn get_callable(&self) -> Option<Rc<dyn Callable>> {
         match *self {
             LoxType::Func(ref func) => Some(func.clone()),
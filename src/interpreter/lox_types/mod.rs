@@ -1,15 +1,29 @@
-use super::{Interpreter,EvaluationError};
+use super::{Interpreter,EvaluationError,Token};
 
 pub mod lox_type;
 pub mod lox_func;
 pub mod lox_class;
 pub mod lox_instance;
+pub mod lox_bigint;
+pub mod lox_decimal;
+pub mod lox_iterator;
+pub mod lox_generator;
+pub mod lox_list;
+pub mod lox_trait;
+pub mod lox_enum;
 
 pub use lox_type::LoxType;
 pub use lox_type::LoxTypeError;
 pub use lox_func::LoxFunc;
 pub use lox_class::{LoxClass,LoxClassInternal};
 pub use lox_instance::LoxInstance;
+pub use lox_bigint::LoxBigInt;
+pub use lox_decimal::LoxDecimal;
+pub use lox_iterator::LoxIterator;
+pub use lox_generator::LoxGenerator;
+pub use lox_list::LoxList;
+pub use lox_trait::LoxTrait;
+pub use lox_enum::{LoxEnum, LoxVariant, VariantDef};
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum FuncType {
@@ -26,7 +40,59 @@ impl std::fmt::Display for FuncType {
     }
 }
 
+/// The number of arguments a callable accepts, with no maximum for variadic callables.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub fn exact(count: usize) -> Arity {
+        Arity { min: count, max: Some(count) }
+    }
+
+    pub fn range(min: usize, max: usize) -> Arity {
+        Arity { min, max: Some(max) }
+    }
+
+    pub fn at_least(min: usize) -> Arity {
+        Arity { min, max: None }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
 pub trait Callable: std::fmt::Debug + std::fmt::Display {
     fn call(&self, interpreter: &mut Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError>;
-    fn arity(&self) -> usize;
+    fn arity(&self) -> Arity;
+
+    /// Calls with named arguments alongside the positional ones.
+    /// Only callables with named parameters support them, so by default any are rejected.
+    fn call_with_named(&self, interpreter: &mut Interpreter, arguments: &[LoxType], named: &[(Token, LoxType)]) -> Result<LoxType, EvaluationError> {
+        match named.first() {
+            Some((name, _)) => Err(EvaluationError::ArgumentError(
+                Some(name.clone()),
+                format!("{} doesn't take named arguments", self)
+            )),
+            None => self.call(interpreter, arguments)
+        }
+    }
+
+    /// The Lox function behind this callable, if it is one.
+    fn as_function(&self) -> Option<&LoxFunc> {
+        None
+    }
 }
\ No newline at end of file
@@ -0,0 +1,445 @@
+use super::super::{Parser, Resolver, Scanner, Stmt, Token};
+use super::super::resolver::{Declaration, DeclarationKind, Reference};
+use super::super::token::TokenType;
+
+use std::collections::HashSet;
+
+const KEYWORDS: [&str; 26] = [
+    "and", "assert", "case", "class", "const", "else", "enum", "false", "for", "fun", "if", "in",
+    "is", "match", "nil", "or", "print", "return", "super", "this", "trait", "true", "var", "while",
+    "with", "yield",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a document. Lines are counted from 1 like tokens'.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Function,
+    Class,
+    Method,
+    Trait,
+    Enum,
+    Variant,
+}
+
+/// A named item of the document's outline, spanning from its name to its closing brace.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: Token,
+    pub kind: SymbolKind,
+    pub end: Token,
+    pub children: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompletionKind {
+    Keyword,
+    Native,
+    Declaration(DeclarationKind),
+    Method,
+    Field,
+    Variant,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+}
+
+/// What the language server knows about one version of a document:
+/// its tokens, syntax tree, resolved names and the problems found compiling it.
+///
+/// Positions passed to the queries are a 1-based line and a 0-based column
+/// counted in bytes, the same as tokens'.
+pub struct Analysis {
+    lines: Vec<String>,
+    tokens: Vec<Token>,
+    stmts: Vec<Stmt>,
+    declarations: Vec<Declaration>,
+    references: Vec<Reference>,
+    diagnostics: Vec<Diagnostic>,
+    natives: HashSet<String>,
+    // Whether the document parsed, so that names were resolved
+    parsed: bool,
+}
+
+impl Analysis {
+    /// Compiles a document as far as it can, given the names of the interpreter's natives.
+    pub fn new(source: &str, natives: HashSet<String>) -> Analysis {
+        let mut analysis = Analysis {
+            lines: source.lines().map(String::from).collect(),
+            tokens: Vec::new(),
+            stmts: Vec::new(),
+            declarations: Vec::new(),
+            references: Vec::new(),
+            diagnostics: Vec::new(),
+            natives,
+            parsed: false,
+        };
+
+        let mut scanner = Scanner::new(source);
+        analysis.tokens = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                let length = source.lines().nth(e.line - 1).map_or(0, str::len);
+                analysis.diagnostics.push(Diagnostic {
+                    line: e.line,
+                    column: 0,
+                    length,
+                    severity: Severity::Error,
+                    message: e.message,
+                });
+                return analysis;
+            }
+        };
+
+        let mut parser = Parser::new(analysis.tokens.clone());
+        analysis.stmts = match parser.parse() {
+            Ok(stmts) => stmts,
+            Err(e) => {
+                let eof = analysis.tokens.last().unwrap();
+                let token = e.token().unwrap_or(eof).clone();
+                analysis.diagnostic(&token, Severity::Error, &e.to_string());
+                return analysis;
+            }
+        };
+
+        analysis.parsed = true;
+        let mut resolver = Resolver::new();
+        if let Err(errors) = resolver.resolve_ast(&mut analysis.stmts) {
+            for error in errors {
+                analysis.diagnostic(error.token(), Severity::Error, &error.to_string());
+            }
+        }
+        // Undefined globals only stop a script running in strict mode
+        for error in resolver.check_globals(&analysis.natives) {
+            analysis.diagnostic(error.token(), Severity::Warning, &error.to_string());
+        }
+        for warning in resolver.warnings() {
+            if !scanner.ignored_lines().contains(&warning.token.line) {
+                analysis.diagnostic(&warning.token, Severity::Warning, &warning.to_string());
+            }
+        }
+        analysis.diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+        analysis.declarations = resolver.declarations().to_vec();
+        analysis.references = resolver.references().to_vec();
+        analysis
+    }
+
+    /// Converts a column counted in bytes to one counted in UTF-16 code units,
+    /// which is how the protocol counts them.
+    pub fn utf16_column(&self, line: usize, column: usize) -> usize {
+        let text = self.line(line);
+        let units: usize = text
+            .char_indices()
+            .take_while(|(index, _)| *index < column)
+            .map(|(_, c)| c.len_utf16())
+            .sum();
+        units + column.saturating_sub(text.len())
+    }
+
+    /// Converts a column counted in UTF-16 code units to one counted in bytes.
+    pub fn byte_column(&self, line: usize, character: usize) -> usize {
+        let text = self.line(line);
+        let mut units = 0;
+        for (index, c) in text.char_indices() {
+            if units >= character {
+                return index;
+            }
+            units += c.len_utf16();
+        }
+        text.len() + character.saturating_sub(units)
+    }
+
+    fn line(&self, line: usize) -> &str {
+        line.checked_sub(1).and_then(|index| self.lines.get(index)).map_or("", String::as_str)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn parsed(&self) -> bool {
+        self.parsed
+    }
+
+    /// Where the name at a position was declared.
+    pub fn definition(&self, line: usize, column: usize) -> Option<&Token> {
+        self.declaration_at(line, column).map(|declaration| &self.declarations[declaration].token)
+    }
+
+    /// Every use of the name at a position, optionally with its declaration first.
+    pub fn references(&self, line: usize, column: usize, include_declaration: bool) -> Vec<&Token> {
+        let declaration = match self.declaration_at(line, column) {
+            Some(declaration) => declaration,
+            None => return Vec::new(),
+        };
+
+        let mut tokens = Vec::new();
+        if include_declaration {
+            tokens.push(&self.declarations[declaration].token);
+        }
+        tokens.extend(self.references
+            .iter()
+            .filter(|reference| reference.declaration == Some(declaration))
+            .map(|reference| &reference.token));
+        tokens
+    }
+
+    /// Describes the name at a position, such as `local variable count` or `global function main`.
+    pub fn hover(&self, line: usize, column: usize) -> Option<String> {
+        if let Some(declaration) = self.declaration_at(line, column) {
+            let declaration = &self.declarations[declaration];
+            let scope = if declaration.global { "global" } else { "local" };
+            return Some(format!("{} {} {}", scope, declaration.kind, declaration.token.lexeme));
+        }
+
+        let token = self.token_at(line, column)?;
+        let is_native = self.natives.contains(&token.lexeme)
+            && self.references.iter().any(|reference| same_token(&reference.token, token));
+        is_native.then(|| format!("native {}", token.lexeme))
+    }
+
+    /// The functions, classes with their methods, traits and enums with their variants
+    /// declared at the top level.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.stmts.iter().filter_map(|stmt| self.symbol(stmt, SymbolKind::Function)).collect()
+    }
+
+    /// The names that make sense at a position: after a `.` the methods and fields
+    /// a property could be, otherwise the keywords, natives and variables in scope.
+    pub fn completions(&self, line: usize, column: usize) -> Vec<Completion> {
+        // Tokens before the cursor, leaving out the identifier being typed
+        let mut before = self.tokens
+            .iter()
+            .take_while(|token| token.token_type != TokenType::Eof && (token.line, token.column) < (line, column))
+            .count();
+        if before > 0 && is_name(&self.tokens[before - 1]) && token_end(&self.tokens[before - 1]) == (line, column) {
+            before -= 1;
+        }
+
+        let mut completions = if before > 0 && self.tokens[before - 1].token_type == TokenType::Dot {
+            self.property_completions(before - 1)
+        } else {
+            self.name_completions(before)
+        };
+        let mut seen = HashSet::new();
+        completions.retain(|completion| seen.insert(completion.label.clone()));
+        completions
+    }
+
+    fn diagnostic(&mut self, token: &Token, severity: Severity, message: &str) {
+        // Messages start with the line, which the client shows on its own
+        let message = match message.strip_prefix(&format!("[line {}] ", token.line)) {
+            Some(message) => message,
+            None => message,
+        };
+        self.diagnostics.push(Diagnostic {
+            line: token.line,
+            column: token.column,
+            length: token.lexeme.len(),
+            severity,
+            message: message.to_string(),
+        });
+    }
+
+    /// The identifier at a position, including just after its last character.
+    fn token_at(&self, line: usize, column: usize) -> Option<&Token> {
+        self.tokens.iter().find(|token| {
+            is_name(token) && token.line == line && token.column <= column && column <= token.column + token.lexeme.len()
+        })
+    }
+
+    /// The index of the declaration the name at a position declares or refers to.
+    fn declaration_at(&self, line: usize, column: usize) -> Option<usize> {
+        let token = self.token_at(line, column)?;
+        self.declarations
+            .iter()
+            .position(|declaration| same_token(&declaration.token, token))
+            .or_else(|| self.references
+                .iter()
+                .find(|reference| same_token(&reference.token, token))
+                .and_then(|reference| reference.declaration))
+    }
+
+    fn index_of(&self, token: &Token) -> Option<usize> {
+        self.tokens.iter().position(|other| same_token(other, token))
+    }
+
+    /// The index of the brace closing the one at `open`.
+    fn matching_brace(&self, open: usize) -> usize {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(open) {
+            match token.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 1 => return i,
+                TokenType::RightBrace => depth -= 1,
+                _ => ()
+            }
+        }
+        self.tokens.len() - 1
+    }
+
+    /// The index of the brace closing the body that starts after a name, such as a function's.
+    fn body_end(&self, name: &Token) -> usize {
+        let start = self.index_of(name).unwrap_or(0);
+        match self.tokens[start..].iter().position(|token| token.token_type == TokenType::LeftBrace) {
+            Some(open) => self.matching_brace(start + open),
+            None => start,
+        }
+    }
+
+    /// The index of the last token where a local declared at `start` can be used.
+    /// Names declared between parentheses, such as parameters and loop variables,
+    /// are in scope in the block after the closing parenthesis.
+    fn scope_end(&self, start: usize) -> usize {
+        let (mut braces, mut parens) = (0, 0);
+        for (i, token) in self.tokens.iter().enumerate().skip(start) {
+            match token.token_type {
+                TokenType::LeftParen => parens += 1,
+                TokenType::RightParen if parens == 0 && self.tokens[i + 1].token_type == TokenType::LeftBrace => {
+                    return self.matching_brace(i + 1);
+                },
+                TokenType::RightParen if parens == 0 => (),
+                TokenType::RightParen => parens -= 1,
+                TokenType::LeftBrace => braces += 1,
+                TokenType::RightBrace if braces == 0 => return i,
+                TokenType::RightBrace => braces -= 1,
+                _ => ()
+            }
+        }
+        self.tokens.len() - 1
+    }
+
+    fn name_completions(&self, before: usize) -> Vec<Completion> {
+        let mut completions: Vec<Completion> = self.declarations
+            .iter()
+            .filter(|declaration| declaration.global || self.index_of(&declaration.token).is_some_and(|start| {
+                start < before && before <= self.scope_end(start)
+            }))
+            .map(|declaration| Completion {
+                label: declaration.token.lexeme.clone(),
+                kind: CompletionKind::Declaration(declaration.kind),
+            })
+            .collect();
+        // Inner declarations shadow outer ones, so they're offered first
+        completions.reverse();
+
+        let mut natives: Vec<&String> = self.natives.iter().collect();
+        natives.sort();
+        completions.extend(natives.into_iter().map(|native| Completion {
+            label: native.clone(),
+            kind: CompletionKind::Native,
+        }));
+        completions.extend(KEYWORDS.iter().map(|keyword| Completion {
+            label: keyword.to_string(),
+            kind: CompletionKind::Keyword,
+        }));
+        completions
+    }
+
+    /// Completions for the property after the `.` at index `dot`.
+    fn property_completions(&self, dot: usize) -> Vec<Completion> {
+        let object = if dot > 0 { Some(&self.tokens[dot - 1]) } else { None };
+        let mut completions = Vec::new();
+
+        if let Some(object) = object.filter(|object| object.token_type == TokenType::This) {
+            // The enclosing class's methods and the fields its methods set
+            let class = self.stmts.iter().find_map(|stmt| match stmt {
+                Stmt::ClassDecl(name, methods, _, _) if self.index_of(name).is_some_and(|start| {
+                    start < dot && dot < self.body_end(name)
+                }) => Some((name, methods)),
+                _ => None
+            });
+            if let Some((name, methods)) = class {
+                completions.extend(method_names(methods).map(|method| Completion { label: method, kind: CompletionKind::Method }));
+                let (start, end) = (self.index_of(name).unwrap(), self.body_end(name));
+                completions.extend(self.tokens[start..end]
+                    .windows(3)
+                    .filter(|window| window[0].token_type == TokenType::This && window[1].token_type == TokenType::Dot && is_name(&window[2]))
+                    .map(|window| Completion { label: window[2].lexeme.clone(), kind: CompletionKind::Field }));
+            }
+            return completions;
+        }
+
+        for stmt in &self.stmts {
+            match stmt {
+                Stmt::ClassDecl(_, methods, _, _) | Stmt::TraitDecl(_, methods) => {
+                    completions.extend(method_names(methods).map(|method| Completion { label: method, kind: CompletionKind::Method }));
+                },
+                // An enum's variants are only offered after the enum's name
+                Stmt::EnumDecl(name, variants) if object.is_some_and(|object| object.lexeme == name.lexeme) => {
+                    completions.extend(variants.iter().map(|(variant, _)| Completion {
+                        label: variant.lexeme.clone(),
+                        kind: CompletionKind::Variant,
+                    }));
+                },
+                _ => ()
+            }
+        }
+        completions
+    }
+
+    fn symbol(&self, stmt: &Stmt, function_kind: SymbolKind) -> Option<Symbol> {
+        let (name, kind, children) = match stmt {
+            Stmt::Function(name, _, _) => (name, function_kind, Vec::new()),
+            Stmt::ClassDecl(name, methods, _, _) => (name, SymbolKind::Class, self.method_symbols(methods)),
+            Stmt::TraitDecl(name, methods) => (name, SymbolKind::Trait, self.method_symbols(methods)),
+            Stmt::EnumDecl(name, variants) => {
+                let variants = variants.iter().map(|(variant, _)| Symbol {
+                    name: variant.clone(),
+                    kind: SymbolKind::Variant,
+                    end: variant.clone(),
+                    children: Vec::new(),
+                }).collect();
+                (name, SymbolKind::Enum, variants)
+            },
+            _ => return None
+        };
+        Some(Symbol {
+            name: name.clone(),
+            kind,
+            end: self.tokens[self.body_end(name)].clone(),
+            children,
+        })
+    }
+
+    fn method_symbols(&self, methods: &[Stmt]) -> Vec<Symbol> {
+        methods.iter().filter_map(|method| self.symbol(method, SymbolKind::Method)).collect()
+    }
+}
+
+fn method_names(methods: &[Stmt]) -> impl Iterator<Item = String> + '_ {
+    methods.iter().filter_map(|method| match method {
+        Stmt::Function(name, _, _) => Some(name.lexeme.clone()),
+        _ => None
+    })
+}
+
+fn is_name(token: &Token) -> bool {
+    token.token_type == TokenType::Identifier
+}
+
+/// The line and column just after a token.
+fn token_end(token: &Token) -> (usize, usize) {
+    (token.line, token.column + token.lexeme.len())
+}
+
+fn same_token(a: &Token, b: &Token) -> bool {
+    a.line == b.line && a.column == b.column
+}
@@ -0,0 +1,347 @@
+pub mod json;
+pub mod analysis;
+
+pub use json::Json;
+pub use analysis::{Analysis, CompletionKind, Diagnostic, Severity, Symbol, SymbolKind};
+
+use super::Token;
+use super::resolver::DeclarationKind;
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+const METHOD_NOT_FOUND: i32 = -32601;
+const PARSE_ERROR: i32 = -32700;
+
+struct Document {
+    analysis: Analysis,
+    // The last version that parsed, while the current one doesn't
+    parsed: Option<Analysis>,
+}
+
+impl Document {
+    /// The analysis to answer navigation and completion requests from.
+    /// A document being edited often doesn't parse, so the last version that did is used.
+    fn resolved(&self) -> &Analysis {
+        match &self.parsed {
+            Some(parsed) if !self.analysis.parsed() => parsed,
+            _ => &self.analysis,
+        }
+    }
+}
+
+/// A language server speaking the Language Server Protocol over a pair of streams,
+/// keeping every open document compiled to answer the client's requests.
+pub struct LanguageServer {
+    // Names of the natives, which are in scope in every document
+    natives: HashSet<String>,
+    documents: HashMap<String, Document>,
+    shut_down: bool,
+}
+
+impl LanguageServer {
+    pub fn new(natives: HashSet<String>) -> LanguageServer {
+        LanguageServer {
+            natives,
+            documents: HashMap::new(),
+            shut_down: false,
+        }
+    }
+
+    /// Serves requests until the client sends `exit` or closes the input.
+    /// Returns whether the client asked the server to shut down first.
+    pub fn run<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<bool> {
+        while let Some(content) = read_message(&mut input)? {
+            let message = match Json::parse(&content) {
+                Ok(message) => message,
+                Err(e) => {
+                    write_message(&mut output, &error_response(Json::Null, PARSE_ERROR, &e))?;
+                    continue;
+                }
+            };
+
+            let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+            if method == "exit" {
+                return Ok(self.shut_down);
+            }
+            let params = message.get("params").cloned().unwrap_or(Json::Null);
+            let replies = match message.get("id") {
+                Some(id) => vec![self.request(id.clone(), method, &params)],
+                None => self.notification(method, &params),
+            };
+            for reply in replies {
+                write_message(&mut output, &reply)?;
+            }
+        }
+        Ok(self.shut_down)
+    }
+
+    fn request(&mut self, id: Json, method: &str, params: &Json) -> Json {
+        let result = match method {
+            "initialize" => capabilities(),
+            "shutdown" => {
+                self.shut_down = true;
+                Json::Null
+            },
+            "textDocument/definition" => self.definition(params),
+            "textDocument/references" => self.references(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/documentSymbol" => self.symbols(params),
+            "textDocument/completion" => self.completion(params),
+            _ => return error_response(id, METHOD_NOT_FOUND, &format!("Unknown method {}", method)),
+        };
+        Json::object(vec![("jsonrpc", "2.0".into()), ("id", id), ("result", result)])
+    }
+
+    /// Handles a notification, returning the notifications to send back.
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri = params.get("textDocument").and_then(|document| document.get("uri")).and_then(Json::as_str);
+        let uri = match uri {
+            Some(uri) => uri.to_string(),
+            None => return Vec::new(),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params.get("textDocument").and_then(|document| document.get("text")).and_then(Json::as_str);
+                vec![self.update(&uri, text.unwrap_or(""))]
+            },
+            "textDocument/didChange" => {
+                // Documents are synced in full, so the last change holds the whole text
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                match text {
+                    Some(text) => vec![self.update(&uri, text)],
+                    None => Vec::new(),
+                }
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![publish_diagnostics(&uri, Vec::new())]
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Compiles a new version of a document, returning its diagnostics.
+    fn update(&mut self, uri: &str, text: &str) -> Json {
+        let analysis = Analysis::new(text, self.natives.clone());
+        let diagnostics = analysis.diagnostics().iter().map(|found| diagnostic(&analysis, found)).collect();
+        let diagnostics = publish_diagnostics(uri, diagnostics);
+        let parsed = match self.documents.remove(uri) {
+            Some(document) if !analysis.parsed() && document.analysis.parsed() => Some(document.analysis),
+            Some(document) if !analysis.parsed() => document.parsed,
+            _ => None,
+        };
+        self.documents.insert(uri.to_string(), Document { analysis, parsed });
+        diagnostics
+    }
+
+    /// The document and the token position a request is about, with the column in bytes.
+    fn position<'a>(&'a self, params: &'a Json) -> Option<(&'a str, &'a Analysis, usize, usize)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let position = params.get("position")?;
+        let line = position.get("line")?.as_usize()?;
+        let character = position.get("character")?.as_usize()?;
+        let analysis = self.documents.get(uri)?.resolved();
+        Some((uri, analysis, line + 1, analysis.byte_column(line + 1, character)))
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        match self.position(params) {
+            Some((uri, analysis, line, column)) => match analysis.definition(line, column) {
+                Some(token) => location(uri, analysis, token),
+                None => Json::Null,
+            },
+            None => Json::Null,
+        }
+    }
+
+    fn references(&self, params: &Json) -> Json {
+        let include_declaration = params
+            .get("context")
+            .and_then(|context| context.get("includeDeclaration"))
+            .is_some_and(|include| *include == Json::Bool(true));
+        match self.position(params) {
+            Some((uri, analysis, line, column)) => Json::Array(analysis
+                .references(line, column, include_declaration)
+                .into_iter()
+                .map(|token| location(uri, analysis, token))
+                .collect()),
+            None => Json::Null,
+        }
+    }
+
+    fn hover(&self, params: &Json) -> Json {
+        match self.position(params).and_then(|(_, analysis, line, column)| analysis.hover(line, column)) {
+            Some(text) => Json::object(vec![(
+                "contents",
+                Json::object(vec![("kind", "plaintext".into()), ("value", text.into())]),
+            )]),
+            None => Json::Null,
+        }
+    }
+
+    fn symbols(&self, params: &Json) -> Json {
+        let uri = params.get("textDocument").and_then(|document| document.get("uri")).and_then(Json::as_str);
+        match uri.and_then(|uri| self.documents.get(uri)) {
+            Some(document) => {
+                let analysis = document.resolved();
+                Json::Array(analysis.symbols().iter().map(|found| symbol(analysis, found)).collect())
+            },
+            None => Json::Null,
+        }
+    }
+
+    fn completion(&self, params: &Json) -> Json {
+        match self.position(params) {
+            Some((_, analysis, line, column)) => Json::Array(analysis
+                .completions(line, column)
+                .into_iter()
+                .map(|completion| Json::object(vec![
+                    ("label", completion.label.into()),
+                    ("kind", completion_kind(completion.kind).into()),
+                ]))
+                .collect()),
+            None => Json::Null,
+        }
+    }
+}
+
+/// Reads the content of the next message, or None at the end of the input.
+/// Fails on a header without a valid length, after which the input can't be followed.
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut headers = false;
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if headers {
+                break;
+            }
+            continue;
+        }
+        headers = true;
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            let value = value.trim().parse::<usize>().map_err(|_| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid Content-Length: {}", value.trim())
+            ))?;
+            length = Some(value);
+        }
+    }
+
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length"))?;
+    let mut content = vec![0; length];
+    input.read_exact(&mut content)?;
+    Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+}
+
+fn write_message<W: Write>(output: &mut W, message: &Json) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+    output.flush()
+}
+
+fn error_response(id: Json, code: i32, message: &str) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("id", id),
+        ("error", Json::object(vec![("code", Json::Number(code as f64)), ("message", message.into())])),
+    ])
+}
+
+fn capabilities() -> Json {
+    Json::object(vec![
+        ("capabilities", Json::object(vec![
+            // Full document sync
+            ("textDocumentSync", 1.into()),
+            ("definitionProvider", true.into()),
+            ("referencesProvider", true.into()),
+            ("hoverProvider", true.into()),
+            ("documentSymbolProvider", true.into()),
+            ("completionProvider", Json::object(vec![("triggerCharacters", Json::Array(vec![".".into()]))])),
+        ])),
+        ("serverInfo", Json::object(vec![("name", "rustylox".into())])),
+    ])
+}
+
+fn diagnostic(analysis: &Analysis, diagnostic: &Diagnostic) -> Json {
+    let end = diagnostic.column + diagnostic.length;
+    Json::object(vec![
+        ("range", range(analysis, diagnostic.line, diagnostic.column, diagnostic.line, end)),
+        ("severity", match diagnostic.severity {
+            Severity::Error => 1.into(),
+            Severity::Warning => 2.into(),
+        }),
+        ("source", "rustylox".into()),
+        ("message", diagnostic.message.clone().into()),
+    ])
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json {
+    Json::object(vec![
+        ("jsonrpc", "2.0".into()),
+        ("method", "textDocument/publishDiagnostics".into()),
+        ("params", Json::object(vec![("uri", uri.into()), ("diagnostics", Json::Array(diagnostics))])),
+    ])
+}
+
+/// A range between two token positions, whose lines count from 1 and columns in bytes.
+fn range(analysis: &Analysis, start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> Json {
+    let position = |line: usize, column: usize| Json::object(vec![
+        ("line", line.saturating_sub(1).into()),
+        ("character", analysis.utf16_column(line, column).into()),
+    ]);
+    Json::object(vec![("start", position(start_line, start_column)), ("end", position(end_line, end_column))])
+}
+
+fn token_range(analysis: &Analysis, token: &Token) -> Json {
+    range(analysis, token.line, token.column, token.line, token.column + token.lexeme.len())
+}
+
+fn location(uri: &str, analysis: &Analysis, token: &Token) -> Json {
+    Json::object(vec![("uri", uri.into()), ("range", token_range(analysis, token))])
+}
+
+fn symbol(analysis: &Analysis, symbol: &Symbol) -> Json {
+    let kind: usize = match symbol.kind {
+        SymbolKind::Function => 12,
+        SymbolKind::Class => 5,
+        SymbolKind::Method => 6,
+        SymbolKind::Trait => 11,
+        SymbolKind::Enum => 10,
+        SymbolKind::Variant => 22,
+    };
+    let end = &symbol.end;
+    Json::object(vec![
+        ("name", symbol.name.lexeme.as_str().into()),
+        ("kind", kind.into()),
+        ("range", range(analysis, symbol.name.line, symbol.name.column, end.line, end.column + end.lexeme.len())),
+        ("selectionRange", token_range(analysis, &symbol.name)),
+        ("children", Json::Array(symbol.children.iter().map(|child| self::symbol(analysis, child)).collect())),
+    ])
+}
+
+fn completion_kind(kind: CompletionKind) -> usize {
+    match kind {
+        CompletionKind::Method => 2,
+        CompletionKind::Native | CompletionKind::Declaration(DeclarationKind::Function) => 3,
+        CompletionKind::Field => 5,
+        CompletionKind::Declaration(DeclarationKind::Variable | DeclarationKind::Parameter) => 6,
+        CompletionKind::Declaration(DeclarationKind::Class) => 7,
+        CompletionKind::Declaration(DeclarationKind::Trait) => 8,
+        CompletionKind::Declaration(DeclarationKind::Enum) => 13,
+        CompletionKind::Keyword => 14,
+        CompletionKind::Variant => 20,
+        CompletionKind::Declaration(DeclarationKind::Constant) => 21,
+    }
+}
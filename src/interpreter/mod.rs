@@ -8,6 +8,12 @@ pub mod stmt;
 pub mod environment;
 pub mod native;
 pub mod resolver;
+pub mod pattern;
+pub mod optimizer;
+pub mod formatter;
+pub mod dump;
+pub mod lsp;
+pub mod debugger;
 
 pub use token::Token;
 pub use scanner::Scanner;
@@ -16,13 +22,19 @@ pub use expr::Expr;
 pub use parser::Parser;
 pub use lox_types::LoxType;
 pub use lox_types::LoxTypeError;
-pub use stmt::Stmt;
+pub use stmt::{Stmt, Param};
 pub use environment::Environment;
-pub use lox_types::{Callable, LoxFunc, LoxClass, LoxClassInternal, LoxInstance};
+pub use lox_types::{Callable, LoxFunc, LoxClass, LoxClassInternal, LoxInstance, LoxBigInt, LoxDecimal, LoxIterator, LoxGenerator, LoxList, LoxTrait, LoxEnum, VariantDef, Arity};
 pub use resolver::Resolver;
+pub use pattern::{Pattern, MatchArm, Destructure};
+pub use optimizer::Optimizer;
+pub use formatter::Formatter;
+pub use dump::{dump_tokens, dump_tree, dump_json};
+pub use lsp::LanguageServer;
+pub use debugger::Debugger;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use token::TokenType;
@@ -44,17 +56,53 @@ pub trait ErrorReport {
 }
 
 type EvaluationResult<T> = Result<T, EvaluationError>;
+
+/// Attaches the token of the call, or of the statement converting a value to a
+/// string, to errors raised without a location.
+fn at_call_site(error: EvaluationError, site: &Token) -> EvaluationError {
+    match error {
+        EvaluationError::NativeError(None, message) =>
+            EvaluationError::NativeError(Some(site.clone()), message),
+        EvaluationError::ArgumentError(None, message) =>
+            EvaluationError::ArgumentError(Some(site.clone()), message),
+        EvaluationError::ConversionError(None, message) =>
+            EvaluationError::ConversionError(Some(site.clone()), message),
+        e => e
+    }
+}
 type StatementResult = Result<Option<LoxType>, EvaluationError>;
+type SelectedArm<'a> = Option<(&'a MatchArm, Rc<RefCell<Environment>>)>;
+type CallParts = (Rc<dyn Callable>, Vec<LoxType>, Vec<(Token, LoxType)>);
+
+/// Name of the binding holding a class's id in its methods' closure.
+/// It can't clash with a variable since identifiers never start with `#`.
+const PRIVATE_OWNER: &str = "#class";
+
+fn private_key(name: &str, class_id: i64) -> String {
+    format!("{}@{}", name, class_id)
+}
 
 #[derive(Debug)]
 pub enum EvaluationError {
-    IllegalExpressionType(Expr),
-    IllegalStatementType(Stmt),
+    IllegalExpressionType(Box<Expr>),
+    IllegalStatementType(Box<Stmt>),
     IllegalOperationError(Token),
     LoxTypeError(Token,LoxTypeError),
     UndefinedIdentifierError(Token),
     CallOnNonCallable(Token),
-    WrongArity(Token,usize,usize),
+    NotIterable(Token),
+    AssignToConstant(Token),
+    WrongArity(Token,usize,Arity),
+    ArgumentError(Option<Token>,String),
+    InvalidClass(Token,String),
+    InvalidPattern(Token,String),
+    DestructureError(Token,String),
+    ConversionError(Option<Token>,String),
+    PrivateAccess(Token,String),
+    AssertionFailed(Token,String),
+    NativeError(Option<Token>,String),
+    // Raised when the user quits the debugger, to stop the script
+    Quit,
     UnknownError,
 }
 
@@ -79,9 +127,49 @@ impl std::fmt::Display for EvaluationError {
             EvaluationError::CallOnNonCallable(ref token) => {
                 write!(f,"[line {}] CallOnNonCallable with {}", token.line, token.lexeme)
             }
+            EvaluationError::NotIterable(ref token) => {
+                write!(f,"[line {}] NotIterable: value can't be iterated with {}", token.line, token.lexeme)
+            }
+            EvaluationError::AssignToConstant(ref token) => {
+                write!(f,"[line {}] AssignToConstant with {}", token.line, token.lexeme)
+            }
             EvaluationError::WrongArity(ref token, len, arity) => {
                 write!(f,"[line {}] WrongArity with {}.  Had {}, expected {}", token.line, token.lexeme, len, arity)
             }
+            EvaluationError::ArgumentError(Some(ref token), ref message) => {
+                write!(f,"[line {}] ArgumentError: {}", token.line, message)
+            }
+            EvaluationError::ArgumentError(None, ref message) => {
+                write!(f,"[line ?] ArgumentError: {}", message)
+            }
+            EvaluationError::InvalidClass(ref token, ref message) => {
+                write!(f,"[line {}] InvalidClass {}: {}", token.line, token.lexeme, message)
+            }
+            EvaluationError::InvalidPattern(ref token, ref message) => {
+                write!(f,"[line {}] InvalidPattern: {}", token.line, message)
+            }
+            EvaluationError::DestructureError(ref token, ref message) => {
+                write!(f,"[line {}] DestructureError: {}", token.line, message)
+            }
+            EvaluationError::ConversionError(Some(ref token), ref message) => {
+                write!(f,"[line {}] ConversionError: {}", token.line, message)
+            }
+            EvaluationError::ConversionError(None, ref message) => {
+                write!(f,"[line ?] ConversionError: {}", message)
+            }
+            EvaluationError::PrivateAccess(ref token, ref message) => {
+                write!(f,"[line {}] PrivateAccess: {}", token.line, message)
+            }
+            EvaluationError::AssertionFailed(ref token, ref message) => {
+                write!(f,"[line {}] AssertionFailed: {}", token.line, message)
+            }
+            EvaluationError::NativeError(Some(ref token), ref message) => {
+                write!(f,"[line {}] NativeError: {}", token.line, message)
+            }
+            EvaluationError::NativeError(None, ref message) => {
+                write!(f,"[line ?] NativeError: {}", message)
+            }
+            EvaluationError::Quit => write!(f,"Quit"),
             EvaluationError::UnknownError => write!(f,"[line ?] UnknownError"),
         }
     }
@@ -96,7 +184,18 @@ impl std::error::Error for EvaluationError {
             EvaluationError::LoxTypeError(_,_) => "LoxTypeError",
             EvaluationError::UndefinedIdentifierError(_) => "UndefinedIdentifierError",
             EvaluationError::CallOnNonCallable(_) => "CallOnNonCallable",
+            EvaluationError::NotIterable(_) => "NotIterable",
+            EvaluationError::AssignToConstant(_) => "AssignToConstant",
             EvaluationError::WrongArity(_,_,_) => "WrongArity",
+            EvaluationError::ArgumentError(_,_) => "ArgumentError",
+            EvaluationError::InvalidClass(_,_) => "InvalidClass",
+            EvaluationError::InvalidPattern(_,_) => "InvalidPattern",
+            EvaluationError::DestructureError(_,_) => "DestructureError",
+            EvaluationError::ConversionError(_,_) => "ConversionError",
+            EvaluationError::PrivateAccess(_,_) => "PrivateAccess",
+            EvaluationError::AssertionFailed(_,_) => "AssertionFailed",
+            EvaluationError::NativeError(_,_) => "NativeError",
+            EvaluationError::Quit => "Quit",
             EvaluationError::UnknownError => "UnknownError",
         }
     }
@@ -104,7 +203,31 @@ impl std::error::Error for EvaluationError {
 
 pub struct Interpreter {
     environment: Rc<RefCell<Environment>>,
-    globals: Rc<RefCell<Environment>>
+    globals: Rc<RefCell<Environment>>,
+    // Instances whose toString() is running, so printing `this` inside it
+    // falls back to the default representation instead of recursing forever
+    converting: Vec<Rc<RefCell<LoxInstance>>>,
+    // Lists being converted, so a list that contains itself shows `[...]` there
+    converting_lists: Vec<Rc<LoxList>>,
+    // Names of the class declarations evaluated.  A class's position, counting
+    // from 1, is its id, which tells apart the private members of different classes
+    class_names: Vec<String>,
+    // Whether a `return` may hand its call back to the caller as a tail call,
+    // true only directly inside a function body that isn't a generator or initializer
+    tail_calls: bool,
+    // The call a `return` left to be made once its function has unwound
+    pending_tail_call: Option<TailCall>,
+    // Pauses the program at breakpoints and steps when debugging a script
+    debugger: Option<Debugger>,
+}
+
+/// A call in tail position, evaluated but not yet made.
+#[derive(Debug)]
+struct TailCall {
+    callee: Rc<dyn Callable>,
+    arguments: Vec<LoxType>,
+    named: Vec<(Token, LoxType)>,
+    paren: Token,
 }
 
 impl Interpreter {
@@ -113,42 +236,108 @@ impl Interpreter {
         
         Interpreter {
             environment: globals.clone(),
-            globals: globals.clone() 
+            globals: globals.clone(),
+            converting: Vec::new(),
+            converting_lists: Vec::new(),
+            class_names: Vec::new(),
+            tail_calls: false,
+            pending_tail_call: None,
+            debugger: None,
         }
     }
 
+    /// Names of the globals defined so far, including natives.
+    pub fn global_names(&self) -> HashSet<String> {
+        self.globals.borrow().names()
+    }
+
     pub fn interpret(&mut self, stmts: Vec<Stmt>) {
-        stmts.iter().for_each(|stmt| {
-            let result = self.evaluate_stmt(stmt);  
-            match result {
+        for stmt in &stmts {
+            match self.evaluate_stmt(stmt) {
                 Ok(_) => (),
+                Err(EvaluationError::Quit) => break,
                 Err(e) => println!("{}", e)
-            };           
-        });      
+            };
+        }
+    }
+
+    /// Runs statements at the top level, stopping at the first error.
+    pub fn run(&mut self, stmts: &[Stmt]) -> EvaluationResult<()> {
+        for stmt in stmts {
+            self.evaluate_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the body of a test block in a scope enclosed by the globals.
+    pub fn run_test(&mut self, body: &[Stmt]) -> EvaluationResult<()> {
+        self.execute_block(body, Environment::from(self.globals.clone()))?;
+        Ok(())
     }
 
     fn evaluate_stmt(&mut self, stmt: &Stmt) -> StatementResult {
+        if self.debugger.is_some() {
+            self.debug_stmt(stmt)?;
+        }
         match stmt {
-            Stmt::PrintStmt(expr) => {
-                println!("{}",self.evaluate_expr(expr)?);
-                Ok(None)
-            },
+            Stmt::PrintStmt(keyword, expr) => self.evaluate_print_stmt(keyword, expr),
+            Stmt::Assert(keyword, condition, message, source) =>
+                self.evaluate_assert_stmt(keyword, condition, message, source),
+            // Tests only run through `Interpreter::run_test`
+            Stmt::Test(_, _, _) => Ok(None),
             Stmt::ExprStmt(expr) => {
                 self.evaluate_expr(expr)?;
                 Ok(None)
             },
             Stmt::VarDecl(_, _) => self.evaluate_var_stmt(stmt),
+            Stmt::VarDestructure(pattern, initializer) =>
+                self.evaluate_var_destructure_stmt(pattern, initializer),
+            Stmt::AssignDestructure(pattern, value, distances) =>
+                self.evaluate_assign_destructure_stmt(pattern, value, distances),
+            Stmt::ConstDecl(name, initializer) => {
+                let value = self.evaluate_expr(initializer)?;
+                self.define(name, &value, true)?;
+                Ok(None)
+            },
             Stmt::Block(stmts) => self.evaluate_block_stmt(stmts),
             Stmt::If(condition, then, else_stmt) =>
                 self.evaluate_if_stmt(condition, then, else_stmt),
             Stmt::While(condition, body) => self.evaluate_while_stmt(condition, body),
+            Stmt::ForIn(keyword, name, iterable, body) =>
+                self.evaluate_for_in_stmt(keyword, name, iterable, body),
             Stmt::Function(name, arguments, body) => 
                 self.evaluate_function_stmt(name, arguments, body),
             Stmt::Return(token, value) => self.evaluate_return_stmt(token, value),
-            Stmt::ClassDecl(name, methods, superclass) => self.evaluate_class_stmt(name, methods, superclass),
+            Stmt::ClassDecl(name, methods, superclass, traits) =>
+                self.evaluate_class_stmt(name, methods, superclass, traits),
+            Stmt::TraitDecl(name, methods) => self.evaluate_trait_stmt(name, methods),
+            Stmt::EnumDecl(name, variants) => self.evaluate_enum_stmt(name, variants),
+            Stmt::Match(_, subject, arms) => self.evaluate_match_stmt(subject, arms),
         }
     }
 
+    // Statements are kept out of `evaluate_stmt`, whose frame is on the stack once per nested call
+    #[inline(never)]
+    fn evaluate_print_stmt(&mut self, keyword: &Token, expr: &Expr) -> StatementResult {
+        let value = self.evaluate_expr(expr)?;
+        println!("{}", self.stringify(&value).map_err(|e| at_call_site(e, keyword))?);
+        Ok(None)
+    }
+
+    #[inline(never)]
+    fn evaluate_assert_stmt(&mut self, keyword: &Token, condition: &Expr, message: &Option<Box<Expr>>, source: &str) -> StatementResult {
+        if self.evaluate_expr(condition)?.is_truthy() {
+            return Ok(None);
+        }
+        let mut failure = format!("assert {}", source);
+        if let Some(message) = message {
+            let message = self.evaluate_expr(message)?;
+            let message = self.stringify(&message).map_err(|e| at_call_site(e, keyword))?;
+            failure = format!("{}: {}", failure, message);
+        }
+        Err(EvaluationError::AssertionFailed(keyword.clone(), failure))
+    }
+
     fn evaluate_var_stmt(&mut self, stmt: &Stmt) -> StatementResult {
         let mut value = LoxType::Nil;
 
@@ -161,10 +350,88 @@ impl Interpreter {
             value = self.evaluate_expr(initializer)?;
         }
 
-        self.environment.borrow_mut().define(&name.lexeme, &value);
+        self.define(name, &value, false)?;
         Ok(None)
     }
 
+    #[inline(never)]
+    fn evaluate_var_destructure_stmt(&mut self, pattern: &Destructure, initializer: &Expr) -> StatementResult {
+        let value = self.evaluate_expr(initializer)?;
+        for (name, value) in self.destructure(pattern, value)? {
+            self.define(name, &value, false)?;
+        }
+        Ok(None)
+    }
+
+    #[inline(never)]
+    fn evaluate_assign_destructure_stmt(&mut self, pattern: &Destructure, value: &Expr, distances: &[Option<usize>]) -> StatementResult {
+        let value = self.evaluate_expr(value)?;
+        let values = self.destructure(pattern, value)?;
+        for ((name, value), distance) in values.into_iter().zip(distances) {
+            self.assign(name, value, *distance)?;
+        }
+        Ok(None)
+    }
+
+    /// Takes the values bound by a destructuring pattern out of `value`, paired with their names.
+    fn destructure<'a>(&mut self, pattern: &'a Destructure, value: LoxType) -> EvaluationResult<Vec<(&'a Token, LoxType)>> {
+        match pattern {
+            Destructure::Tuple(paren, names) => {
+                let mut iterator = LoxIterator::new(self, value, paren)?;
+                let mut values = Vec::new();
+                for name in names {
+                    match iterator.next(self, paren)? {
+                        Some(item) => values.push((name, item)),
+                        None => return Err(EvaluationError::DestructureError(
+                            paren.clone(),
+                            format!("expected {} values to unpack into {} but got {}", names.len(), pattern, values.len())
+                        ))
+                    }
+                }
+                if iterator.next(self, paren)?.is_some() {
+                    return Err(EvaluationError::DestructureError(
+                        paren.clone(),
+                        format!("too many values to unpack into {}", pattern)
+                    ));
+                }
+                Ok(values.into_iter().filter(|(name, _)| name.lexeme != "_").collect())
+            },
+            Destructure::Fields(brace, _) => {
+                pattern.bindings().into_iter().map(|name| {
+                    let field = match value {
+                        LoxType::Instance(ref instance) => instance.borrow().field(&name.lexeme),
+                        LoxType::Variant(ref variant) => variant.field(&name.lexeme),
+                        _ => return Err(EvaluationError::DestructureError(
+                            brace.clone(),
+                            format!("{} has no fields to unpack into {}", value, pattern)
+                        ))
+                    };
+                    field
+                        .map(|field| (name, field))
+                        .ok_or_else(|| EvaluationError::DestructureError(
+                            name.clone(),
+                            format!("{} has no field {}", value, name.lexeme)
+                        ))
+                }).collect()
+            }
+        }
+    }
+
+    /// Defines a name in the current environment, refusing to replace a constant.
+    fn define(&self, name: &Token, value: &LoxType, constant: bool) -> EvaluationResult<()> {
+        let mut environment = self.environment.borrow_mut();
+        if environment.is_constant(&name.lexeme) {
+            return Err(EvaluationError::AssignToConstant(name.clone()));
+        }
+
+        if constant {
+            environment.define_constant(&name.lexeme, value);
+        } else {
+            environment.define(&name.lexeme, value);
+        }
+        Ok(())
+    }
+
     fn evaluate_block_stmt(&mut self, stmts: &[Stmt]) -> StatementResult {
         let env = Environment::from(
             self.environment.clone()
@@ -173,7 +440,10 @@ impl Interpreter {
     }
 
     fn execute_block(&mut self, stmts: &[Stmt], environment: Environment) -> StatementResult {
-        let environment = Rc::new(RefCell::new(environment));
+        self.execute_block_in(stmts, Rc::new(RefCell::new(environment)))
+    }
+
+    fn execute_block_in(&mut self, stmts: &[Stmt], environment: Rc<RefCell<Environment>>) -> StatementResult {
         let previous = std::mem::replace(&mut self.environment, environment);
 
         for stmt in stmts {
@@ -214,7 +484,22 @@ impl Interpreter {
         Ok(None)
     }
 
-    fn evaluate_function_stmt(&self, name: &Token, arguments: &Vec<Token>, body: &Vec<Stmt>) -> StatementResult {
+    fn evaluate_for_in_stmt(&mut self, keyword: &Token, name: &Token, iterable: &Expr, body: &Stmt) -> StatementResult {
+        let iterable = self.evaluate_expr(iterable)?;
+        let mut iterator = LoxIterator::new(self, iterable, keyword)?;
+
+        while let Some(item) = iterator.next(self, keyword)? {
+            // Each iteration gets a fresh variable so closures capture the current item
+            let mut environment = Environment::from(self.environment.clone());
+            environment.define(&name.lexeme, &item);
+            if let Some(ret) = self.execute_block(std::slice::from_ref(body), environment)? {
+                return Ok(Some(ret));
+            }
+        }
+        Ok(None)
+    }
+
+    fn evaluate_function_stmt(&self, name: &Token, arguments: &Vec<Param>, body: &Vec<Stmt>) -> StatementResult {
         let function = LoxFunc::new(
             name.clone(),
             arguments.clone(),
@@ -222,13 +507,23 @@ impl Interpreter {
             self.environment.clone(),
             false
         );
-        self.environment.borrow_mut().define(&name.lexeme, &LoxType::Func(Rc::new(function)));
+        self.define(name, &LoxType::Func(Rc::new(function)), false)?;
         Ok(None)
     }
 
     fn evaluate_return_stmt(&mut self, token: &Token, value: &Option<Expr>) -> StatementResult {
         let mut expr_result: Option<LoxType> = Some(LoxType::Nil);
 
+        // The call is made by the caller's trampoline after this function
+        // returns, so tail recursion doesn't grow the stack
+        if let Some(Expr::Call(callee, paren, arguments, named)) = value {
+            if self.tail_calls {
+                let (callee, arguments, named) = self.evaluate_call_parts(callee, paren, arguments, named)?;
+                self.pending_tail_call = Some(TailCall { callee, arguments, named, paren: paren.clone() });
+                return Ok(expr_result);
+            }
+        }
+
         if let Some(value) = value {
             expr_result = Some(self.evaluate_expr(value)?);
         }
@@ -236,7 +531,7 @@ impl Interpreter {
         Ok(expr_result)
     }
 
-    fn evaluate_class_stmt(&mut self, name: &Token, methods: &[Stmt], superclass: &Option<Expr>) -> StatementResult {
+    fn evaluate_class_stmt(&mut self, name: &Token, methods: &[Stmt], superclass: &Option<Expr>, traits: &[Expr]) -> StatementResult {
         let mut evaluated_superclass = None;
         if let Some(superclass) = superclass {
             let superclass = match self.evaluate_expr(superclass)? {
@@ -246,19 +541,91 @@ impl Interpreter {
             evaluated_superclass = Some(superclass);
         }
 
+        let mut evaluated_traits = Vec::new();
+        for trait_expr in traits {
+            match self.evaluate_expr(trait_expr)? {
+                LoxType::Trait(trait_type) => evaluated_traits.push(trait_type),
+                other => return Err(EvaluationError::InvalidClass(
+                    name.clone(),
+                    format!("{} is not a trait", other)
+                ))
+            }
+        }
 
-        self.environment.borrow_mut().define(&name.lexeme, &LoxType::Nil);
 
-        if let Some(superclass) = superclass {
-            let child = Environment::from(self.environment.clone());
-            self.environment = Rc::new(RefCell::new(child));
+        self.define(name, &LoxType::Nil, false)?;
 
-            let superclass_loxtype = LoxType::Class(evaluated_superclass.as_ref().unwrap().clone());
-            
+        // Methods close over an environment holding `super` and the id
+        // that private members of this class are stored under
+        self.class_names.push(name.lexeme.clone());
+        let class_id = self.class_names.len() as i64;
+        let child = Environment::from(self.environment.clone());
+        self.environment = Rc::new(RefCell::new(child));
+        self.environment.borrow_mut().define(PRIVATE_OWNER, &LoxType::Int(class_id));
+
+        if let Some(superclass) = evaluated_superclass.as_ref() {
+            let superclass_loxtype = LoxType::Class(superclass.clone());
             self.environment.borrow_mut().define("super", &superclass_loxtype);
         }
 
-        let mut class_methods: HashMap<String,LoxFunc> = HashMap::new();
+        let mut class_methods: HashMap<String, LoxFunc> = self.evaluate_methods(methods)
+            .into_iter()
+            .map(|(method_name, method)| match method_name.starts_with('#') {
+                true => (private_key(&method_name, class_id), method),
+                false => (method_name, method)
+            })
+            .collect();
+
+        let enclosing = self.environment.borrow().enclosing.as_ref().unwrap().clone();
+        self.environment = enclosing;
+
+        // Trait methods sit between the class's own methods and the superclass's.
+        // Two traits providing the same method is an error unless the class overrides it.
+        let mut trait_methods: HashMap<String, (LoxFunc, &str)> = HashMap::new();
+        for trait_type in &evaluated_traits {
+            for (method_name, method) in &trait_type.methods {
+                if class_methods.contains_key(method_name) {
+                    continue;
+                }
+                if let Some((_, other)) = trait_methods.get(method_name) {
+                    return Err(EvaluationError::InvalidClass(
+                        name.clone(),
+                        format!("method {} is provided by both {} and {}", method_name, other, trait_type.name)
+                    ));
+                }
+                trait_methods.insert(method_name.clone(), (method.clone(), &trait_type.name));
+            }
+        }
+        class_methods.extend(trait_methods.into_iter().map(|(method_name, (method, _))| (method_name, method)));
+
+        let class = LoxClass::new(&name.lexeme, class_methods, evaluated_superclass, evaluated_traits);
+        self.environment.borrow_mut().assign(&name, LoxType::Class(Rc::new(class)))?;
+
+        Ok(None)
+    }
+
+    fn evaluate_trait_stmt(&mut self, name: &Token, methods: &[Stmt]) -> StatementResult {
+        let trait_type = LoxTrait::new(&name.lexeme, self.evaluate_methods(methods));
+        self.define(name, &LoxType::Trait(Rc::new(trait_type)), false)?;
+        Ok(None)
+    }
+
+    fn evaluate_enum_stmt(&mut self, name: &Token, variants: &[(Token, Vec<Token>)]) -> StatementResult {
+        let variants = variants
+            .iter()
+            .map(|(variant, fields)| VariantDef {
+                name: variant.lexeme.clone(),
+                fields: fields.iter().map(|field| field.lexeme.clone()).collect(),
+            })
+            .collect();
+        let enum_type = LoxEnum::new(&name.lexeme, variants);
+        self.define(name, &LoxType::Enum(Rc::new(enum_type)), false)?;
+        Ok(None)
+    }
+
+    /// Creates the methods of a class or trait, closing over the current environment.
+    fn evaluate_methods(&self, methods: &[Stmt]) -> HashMap<String, LoxFunc> {
+        let mut evaluated: HashMap<String,LoxFunc> = HashMap::new();
         for method in methods {
             if let Stmt::Function(ref name, ref parameters, ref body) = *method {
                 let func = LoxFunc::new(
@@ -268,23 +635,105 @@ impl Interpreter {
                     self.environment.clone(),
                     name.lexeme == "init"
                 );
-                class_methods.insert(name.lexeme.clone(), func);
+                evaluated.insert(name.lexeme.clone(), func);
             }
         }
-        
-
-        let class = LoxClass::new(&name.lexeme, class_methods, evaluated_superclass);
+        evaluated
+    }
 
-        if let Some(superclass) = superclass {
-            let enclosing = self.environment.borrow().enclosing.as_ref().unwrap().clone();
-            self.environment = enclosing;
+    fn evaluate_match_stmt(&mut self, subject: &Expr, arms: &[MatchArm]) -> StatementResult {
+        let subject = self.evaluate_expr(subject)?;
+        match self.select_match_arm(&subject, arms)? {
+            Some((arm, environment)) => self.execute_block_in(std::slice::from_ref(&arm.body), environment),
+            None => Ok(None)
         }
+    }
 
-        self.environment.borrow_mut().assign(&name, LoxType::Class(Rc::new(class)))?;
+    /// Finds the first arm with a pattern matching `subject` and a passing guard,
+    /// returning it with the environment holding the pattern's bindings.
+    fn select_match_arm<'a>(&mut self, subject: &LoxType, arms: &'a [MatchArm]) -> EvaluationResult<SelectedArm<'a>> {
+        for arm in arms {
+            for pattern in &arm.patterns {
+                let mut bindings = Vec::new();
+                if !self.match_pattern(pattern, subject, &mut bindings)? {
+                    continue;
+                }
 
+                let mut environment = Environment::from(self.environment.clone());
+                for (name, value) in bindings {
+                    environment.define(name, &value);
+                }
+                let environment = Rc::new(RefCell::new(environment));
+
+                if let Some(guard) = &arm.guard {
+                    let previous = std::mem::replace(&mut self.environment, environment.clone());
+                    let passed = self.evaluate_expr(guard).map(|value| value.is_truthy());
+                    self.environment = previous;
+                    if !passed? {
+                        continue;
+                    }
+                }
+
+                return Ok(Some((arm, environment)));
+            }
+        }
         Ok(None)
     }
 
+    fn match_pattern<'a>(&mut self, pattern: &'a Pattern, value: &LoxType, bindings: &mut Vec<(&'a str, LoxType)>) -> EvaluationResult<bool> {
+        match pattern {
+            Pattern::Wildcard(_) => Ok(true),
+            Pattern::Binding(name) => {
+                bindings.push((&name.lexeme, value.clone()));
+                Ok(true)
+            },
+            Pattern::Literal(_, literal) => Ok(self.evaluate_literal_expr(literal)? == *value),
+            Pattern::Class(name, class, fields) => {
+                let class = match self.evaluate_expr(class)? {
+                    LoxType::Class(class) => class,
+                    _ => return Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
+                };
+                let instance = match value {
+                    LoxType::Instance(instance) if instance.borrow().is_instance_of(&class) => instance,
+                    _ => return Ok(false)
+                };
+                for field in fields.iter().filter(|field| field.lexeme != "_") {
+                    match instance.borrow().field(&field.lexeme) {
+                        Some(value) => bindings.push((&field.lexeme, value)),
+                        None => return Ok(false)
+                    }
+                }
+                Ok(true)
+            },
+            Pattern::Variant(name, variant_name, enum_expr, fields) => {
+                let enum_type = match self.evaluate_expr(enum_expr)? {
+                    LoxType::Enum(enum_type) => enum_type,
+                    _ => return Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
+                };
+                let index = enum_type.variant_index(&variant_name.lexeme)
+                    .ok_or_else(|| EvaluationError::UndefinedIdentifierError(variant_name.clone()))?;
+                let variant = match value {
+                    LoxType::Variant(variant) if enum_type.is_variant(variant, index) => variant,
+                    _ => return Ok(false)
+                };
+                if let Some(fields) = fields {
+                    if fields.len() != variant.values().len() {
+                        return Err(EvaluationError::InvalidPattern(
+                            variant_name.clone(),
+                            format!("{} has {} fields but the pattern binds {}", pattern, variant.values().len(), fields.len())
+                        ));
+                    }
+                    for (field, value) in fields.iter().zip(variant.values()) {
+                        if field.lexeme != "_" {
+                            bindings.push((&field.lexeme, value.clone()));
+                        }
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
     fn evaluate_expr(&mut self, expr: &Expr) -> EvaluationResult<LoxType> {
         match expr {
             Expr::Literal(literal) => self.evaluate_literal_expr(literal),
@@ -294,11 +743,13 @@ impl Interpreter {
             Expr::Var(identifier, distance) => self.evaluate_var_expr(identifier, *distance),
             Expr::Assign(identifier, value, distance) => self.evaluate_assign_expr(identifier, value, *distance),
             Expr::Logical(left, token, right) => self.evaluate_logical_expr(left, token, right),
-            Expr::Call(callee, paren, arguments) => self.evaluate_call_expr(callee, paren, arguments),
+            Expr::Call(callee, paren, arguments, named) => self.evaluate_call_expr(callee, paren, arguments, named),
             Expr::Get(object, name) => self.evaluate_get_expr(object, name),
             Expr::Set(object, name, value) => self.evaluate_set_expr(object, name, value),
             Expr::This(identifier, distance) => self.evaluate_this_expr(identifier, distance),
             Expr::Super(identifier, method, distance) => self.evaluate_super_expr(identifier, method, *distance),
+            // Yields are only reached when a generator steps through its body
+            Expr::Yield(keyword, _) => Err(EvaluationError::IllegalOperationError(keyword.clone())),
         }
     }
 
@@ -306,7 +757,10 @@ impl Interpreter {
         match literal {
             Literal::Bool(val) => Ok(LoxType::Bool(*val)),
             Literal::Nil => Ok(LoxType::Nil),
+            Literal::Int(ref val) => Ok(LoxType::Int(*val)),
             Literal::Num(ref val) => Ok(LoxType::Num(*val)),
+            Literal::BigInt(ref val) => Ok(LoxType::BigInt(val.clone())),
+            Literal::Decimal(ref val) => Ok(LoxType::Decimal(val.clone())),
             Literal::String(val) => Ok(LoxType::String(val.clone())),
         }
     }
@@ -327,13 +781,35 @@ impl Interpreter {
     }
 
     fn evaluate_binary_expr(&mut self, left: &Expr, token: &Token, right: &Expr) -> EvaluationResult<LoxType> {
-        let left = self.evaluate_expr(left)?;
-        let right = self.evaluate_expr(right)?;
+        let mut left = self.evaluate_expr(left)?;
+        let mut right = self.evaluate_expr(right)?;
+
+        // Concatenating a string with an instance converts it through toString()
+        if token.token_type == TokenType::Plus {
+            if let (LoxType::String(_), LoxType::Instance(instance)) = (&left, &right) {
+                if instance.borrow().find_method("toString").is_some() {
+                    right = LoxType::String(self.stringify(&right).map_err(|e| at_call_site(e, token))?);
+                }
+            }
+            if let (LoxType::Instance(instance), LoxType::String(_)) = (&left, &right) {
+                if instance.borrow().find_method("toString").is_some() {
+                    left = LoxType::String(self.stringify(&left).map_err(|e| at_call_site(e, token))?);
+                }
+            }
+        }
 
         let result = match token.token_type {
             TokenType::Minus => left - right,
             TokenType::Slash => left / right,
             TokenType::Star => left * right,
+            TokenType::Percent => left % right,
+            TokenType::TildeSlash => left.int_div(right),
+            TokenType::Is => return left.is_a(&right)
+                .map(LoxType::Bool)
+                .ok_or_else(|| EvaluationError::InvalidClass(
+                    token.clone(),
+                    format!("{} is not a class, trait or enum", right)
+                )),
             TokenType::Plus => left + right,
             TokenType::Greater |
             TokenType::GreaterEqual |
@@ -350,6 +826,52 @@ impl Interpreter {
         }       
     }    
 
+    /// Converts a value to the string `print` shows, calling an instance's
+    /// `toString()` method when its class defines one, also for the elements of a list.
+    fn stringify(&mut self, value: &LoxType) -> EvaluationResult<String> {
+        let instance = match value {
+            LoxType::Instance(instance) => instance,
+            LoxType::List(list) => return self.stringify_list(list),
+            _ => return Ok(value.to_string())
+        };
+        if self.converting.iter().any(|converting| Rc::ptr_eq(converting, instance)) {
+            return Ok(value.to_string());
+        }
+        let method = match instance.borrow().find_method("toString") {
+            Some(method) => method,
+            None => return Ok(value.to_string())
+        };
+
+        self.converting.push(instance.clone());
+        let result = method.bind(instance.clone()).call(self, &[]);
+        self.converting.pop();
+
+        match result? {
+            LoxType::String(string) => Ok(string),
+            // The caller attaches the line the conversion happened on
+            other => Err(EvaluationError::ConversionError(
+                None,
+                format!("toString() must return a string, got {}", other)
+            ))
+        }
+    }
+
+    /// Converts each element the same way, so a list shows its instances' `toString()`.
+    fn stringify_list(&mut self, list: &Rc<LoxList>) -> EvaluationResult<String> {
+        if self.converting_lists.iter().any(|converting| Rc::ptr_eq(converting, list)) {
+            return Ok(String::from("[...]"));
+        }
+
+        self.converting_lists.push(list.clone());
+        let items: EvaluationResult<Vec<String>> = (0..list.len())
+            .filter_map(|index| list.at(index))
+            .map(|item| self.stringify(&item))
+            .collect();
+        self.converting_lists.pop();
+
+        Ok(format!("[{}]", items?.join(", ")))
+    }
+
     fn evaluate_var_expr(&self, identifier: &Token, distance: Option<usize>) -> EvaluationResult<LoxType> {
         if let Some(distance) = distance {
             self.environment.borrow().get_at(identifier, distance)
@@ -360,6 +882,10 @@ impl Interpreter {
 
     fn evaluate_assign_expr(&mut self, identifier: &Token, value: &Expr, distance: Option<usize>) -> EvaluationResult<LoxType> {
         let value = self.evaluate_expr(value)?;
+        self.assign(identifier, value, distance)
+    }
+
+    fn assign(&mut self, identifier: &Token, value: LoxType, distance: Option<usize>) -> EvaluationResult<LoxType> {
         if let Some(distance) = distance {
             self.environment.borrow_mut().assign_at(identifier, value, distance)
         } else {
@@ -379,7 +905,14 @@ impl Interpreter {
         self.evaluate_expr(right)
     }
 
-    fn evaluate_call_expr(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> EvaluationResult<LoxType> {
+    fn evaluate_call_expr(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr], named: &[(Token, Expr)]) -> EvaluationResult<LoxType> {
+        let (callee, arguments, named) = self.evaluate_call_parts(callee, paren, arguments, named)?;
+        callee.call_with_named(self, &arguments, &named).map_err(|e| at_call_site(e, paren))
+    }
+
+    /// Evaluates the callee and arguments of a call and checks the arity,
+    /// leaving only the call itself to be made.
+    fn evaluate_call_parts(&mut self, callee: &Expr, paren: &Token, arguments: &[Expr], named: &[(Token, Expr)]) -> EvaluationResult<CallParts> {
         let callee = self.evaluate_expr(callee)?
             .get_callable()
             .ok_or_else(|| EvaluationError::CallOnNonCallable(paren.clone()))?;
@@ -388,8 +921,14 @@ impl Interpreter {
         for arg in arguments {
             evaluated_arguments.push(self.evaluate_expr(arg)?);
         }
+
+        let mut evaluated_named: Vec<(Token, LoxType)> = Vec::new();
+        for (name, arg) in named {
+            evaluated_named.push((name.clone(), self.evaluate_expr(arg)?));
+        }
         
-        if arguments.len() != callee.arity() {
+        // With named arguments the callee matches them to its parameters and checks for itself
+        if named.is_empty() && !callee.arity().accepts(arguments.len()) {
             return Err(EvaluationError::WrongArity(
                 paren.clone(),
                 arguments.len(),
@@ -397,34 +936,75 @@ impl Interpreter {
             ));        
         }
 
-        callee.call(self, &evaluated_arguments)
+        Ok((callee, evaluated_arguments, evaluated_named))
     }
 
     fn evaluate_get_expr(&mut self, object: &Expr, name: &Token) -> EvaluationResult<LoxType> {
-        let object = self.evaluate_expr(object)?;
-        if let LoxType::Instance(object) = object {
-            Ok(object.borrow().get(name)?)
-        } else {
-            Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
+        if name.token_type == TokenType::PrivateIdentifier {
+            let key = self.private_member_key(object, name)?;
+            return match self.evaluate_expr(object)? {
+                LoxType::Instance(object) => LoxInstance::get_key(&object, &key, name)
+                    .map_err(|e| self.private_access_error(&object, name).unwrap_or(e)),
+                _ => Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
+            };
+        }
+
+        match self.evaluate_expr(object)? {
+            LoxType::Instance(object) => LoxInstance::get(&object, name),
+            LoxType::Generator(generator) => LoxGenerator::get(&generator, name),
+            LoxType::List(list) => LoxList::get(&list, name),
+            LoxType::Enum(enum_type) => enum_type.get(name),
+            LoxType::Variant(variant) => variant.get(name),
+            _ => Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
         }
     }
 
     fn evaluate_set_expr(&mut self, object: &Expr, name: &Token, value: &Expr) -> EvaluationResult<LoxType> {
+        let key = match name.token_type {
+            TokenType::PrivateIdentifier => self.private_member_key(object, name)?,
+            _ => name.lexeme.clone()
+        };
         let object = self.evaluate_expr(object)?;
         if let LoxType::Instance(object) = object {
             let value = self.evaluate_expr(value)?;
-            object.borrow_mut().set(name, &value);
+            object.borrow_mut().set_field(&key, value.clone());
             Ok(value)
         } else {
             Err(EvaluationError::LoxTypeError(name.clone(), LoxTypeError::IllegalOperationError))
         }
     }
 
+    /// Private members are stored under a key naming the class whose methods
+    /// access them, so a subclass can't reach its superclass's private members.
+    /// The resolver ensures `object` is `this`; the class id sits in the
+    /// environment enclosing the one `this` is bound in.
+    fn private_member_key(&self, object: &Expr, name: &Token) -> EvaluationResult<String> {
+        let distance = match object {
+            Expr::This(_, Some(distance)) => *distance,
+            _ => return Err(EvaluationError::IllegalOperationError(name.clone()))
+        };
+        let owner_token = Token::new(TokenType::Identifier, PRIVATE_OWNER, None, name.line);
+        match self.environment.borrow().get_at(&owner_token, distance + 1)? {
+            LoxType::Int(class_id) => Ok(private_key(&name.lexeme, class_id)),
+            _ => Err(EvaluationError::IllegalOperationError(name.clone()))
+        }
+    }
+
+    /// Explains a failed private lookup when another class, such as a superclass,
+    /// has a private member by that name.
+    fn private_access_error(&self, instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Option<EvaluationError> {
+        let (kind, class_id) = instance.borrow().private_owner(&name.lexeme)?;
+        let class_name = self.class_names.get(usize::try_from(class_id).ok()?.checked_sub(1)?)?;
+        Some(EvaluationError::PrivateAccess(
+            name.clone(),
+            format!("Private {} '{}' is only accessible inside class {}", kind, name.lexeme, class_name)
+        ))
+    }
+
     fn evaluate_this_expr(&mut self, identifier: &Token, distance: &Option<usize>) -> EvaluationResult<LoxType> {
         if let Some(distance) = distance {
             self.environment.borrow().get_at(identifier, *distance)
         } else {
-            println!("{:?}", self.environment.borrow_mut().get(identifier));
             self.globals.borrow().get(identifier)
         }  
     }
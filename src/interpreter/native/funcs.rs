@@ -1,9 +1,16 @@
 use crate::interpreter::{
     LoxType,
     Callable,
+    LoxBigInt,
+    LoxDecimal,
+    LoxList,
+    LoxInstance,
+    Arity,
 };
 use crate::interpreter::EvaluationError;
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 
@@ -22,8 +29,8 @@ impl std::fmt::Display for Clock {
 
 impl Callable for Clock {
 
-    fn arity(&self) -> usize {
-        0
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
     }
 
     fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
@@ -33,4 +40,511 @@ impl Callable for Clock {
             .as_secs();
         Ok(LoxType::Num(current_time as f64))
     } 
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+pub struct Int {}
+
+impl Int {
+    pub fn new() -> Int {Int{}}
+}
+
+impl std::fmt::Display for Int {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Int>")
+    }
+}
+
+impl Callable for Int {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Int(n) => Ok(LoxType::Int(*n)),
+            // Truncates towards zero, rejecting values an i64 cannot hold
+            LoxType::Num(n) if n.is_finite() && n.abs() < 9223372036854775808.0 => {
+                Ok(LoxType::Int(n.trunc() as i64))
+            },
+            LoxType::Num(n) => Err(EvaluationError::NativeError(
+                None,
+                format!("Can't convert {} to an integer", n)
+            )),
+            LoxType::BigInt(n) => n.to_i64()
+                .map(LoxType::Int)
+                .ok_or_else(|| EvaluationError::NativeError(
+                    None,
+                    format!("{} is too large for an integer", n)
+                )),
+            LoxType::Bool(b) => Ok(LoxType::Int(*b as i64)),
+            LoxType::String(s) => s.trim().parse::<i64>()
+                .map(LoxType::Int)
+                .map_err(|_| EvaluationError::NativeError(
+                    None,
+                    format!("Can't convert \"{}\" to an integer", s)
+                )),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("Can't convert {} to an integer", other)
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Float {}
+
+impl Float {
+    pub fn new() -> Float {Float{}}
+}
+
+impl std::fmt::Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Float>")
+    }
+}
+
+impl Callable for Float {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Int(n) => Ok(LoxType::Num(*n as f64)),
+            LoxType::Num(n) => Ok(LoxType::Num(*n)),
+            LoxType::BigInt(n) => Ok(LoxType::Num(n.to_f64())),
+            LoxType::Decimal(n) => Ok(LoxType::Num(n.to_f64())),
+            LoxType::String(s) => s.trim().parse::<f64>()
+                .map(LoxType::Num)
+                .map_err(|_| EvaluationError::NativeError(
+                    None,
+                    format!("Can't convert \"{}\" to a float", s)
+                )),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("Can't convert {} to a float", other)
+            )),
+        }
+    }
+}
+
+
+#[derive(Debug)]
+pub struct BigInt {}
+
+impl BigInt {
+    pub fn new() -> BigInt {BigInt{}}
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn BigInt>")
+    }
+}
+
+impl Callable for BigInt {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Int(n) => Ok(LoxType::from(LoxBigInt::from_i64(*n))),
+            LoxType::BigInt(n) => Ok(LoxType::BigInt(n.clone())),
+            LoxType::Num(n) if n.is_finite() && n.fract() == 0.0 => {
+                Ok(LoxType::from(LoxBigInt::parse(&format!("{}", n)).unwrap()))
+            },
+            LoxType::String(s) => LoxBigInt::parse(s.trim())
+                .map(LoxType::from)
+                .ok_or_else(|| EvaluationError::NativeError(
+                    None,
+                    format!("Can't convert \"{}\" to a big integer", s)
+                )),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("Can't convert {} to a big integer", other)
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Decimal {}
+
+impl Decimal {
+    pub fn new() -> Decimal {Decimal{}}
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Decimal>")
+    }
+}
+
+impl Callable for Decimal {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Int(n) => Ok(LoxType::from(LoxDecimal::from_bigint(LoxBigInt::from_i64(*n)))),
+            LoxType::BigInt(n) => Ok(LoxType::from(LoxDecimal::from_bigint(LoxBigInt::clone(n)))),
+            LoxType::Decimal(n) => Ok(LoxType::Decimal(n.clone())),
+            // Floats convert through their shortest representation, so 0.1 becomes exactly 0.1
+            LoxType::Num(n) if n.is_finite() => {
+                Ok(LoxType::from(LoxDecimal::parse(&format!("{}", n)).unwrap()))
+            },
+            LoxType::String(s) => LoxDecimal::parse(s.trim())
+                .map(LoxType::from)
+                .ok_or_else(|| EvaluationError::NativeError(
+                    None,
+                    format!("Can't convert \"{}\" to a decimal", s)
+                )),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("Can't convert {} to a decimal", other)
+            )),
+        }
+    }
+}
+
+
+#[derive(Debug)]
+pub struct Range {}
+
+impl Range {
+    pub fn new() -> Range {Range{}}
+}
+
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Range>")
+    }
+}
+
+impl Callable for Range {
+
+    fn arity(&self) -> Arity {
+        Arity::range(2, 3)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let step = arguments.get(2).unwrap_or(&LoxType::Int(1));
+        match (&arguments[0], &arguments[1], step) {
+            (LoxType::Int(_), LoxType::Int(_), LoxType::Int(0)) => Err(EvaluationError::NativeError(
+                None,
+                "Range step can't be zero".to_string()
+            )),
+            (LoxType::Int(start), LoxType::Int(end), LoxType::Int(step)) => Ok(LoxType::Range(Rc::new((*start, *end, *step)))),
+            (start, end, step) => Err(EvaluationError::NativeError(
+                None,
+                format!("Range bounds and step must be integers, got {}, {} and {}", start, end, step)
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct List {}
+
+impl List {
+    pub fn new() -> List {List{}}
+}
+
+impl std::fmt::Display for List {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn List>")
+    }
+}
+
+impl Callable for List {
+
+    fn arity(&self) -> Arity {
+        Arity::at_least(0)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        Ok(LoxType::List(Rc::new(LoxList::new(arguments.to_vec()))))
+    }
+}
+
+#[derive(Debug)]
+pub struct Str {}
+
+impl Str {
+    pub fn new() -> Str {Str{}}
+}
+
+impl std::fmt::Display for Str {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Str>")
+    }
+}
+
+impl Callable for Str {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        Ok(LoxType::String(interpreter.stringify(&arguments[0])?))
+    }
+}
+
+#[derive(Debug)]
+pub struct Type {}
+
+impl Type {
+    pub fn new() -> Type {Type{}}
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Type>")
+    }
+}
+
+impl Callable for Type {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        Ok(LoxType::String(arguments[0].type_name().to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub struct ClassOf {}
+
+impl ClassOf {
+    pub fn new() -> ClassOf {ClassOf{}}
+}
+
+impl std::fmt::Display for ClassOf {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn ClassOf>")
+    }
+}
+
+impl Callable for ClassOf {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let instance = instance_arg("classOf", &arguments[0])?;
+        let class = instance.borrow().class();
+        Ok(LoxType::Class(Rc::new(class)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Fields {}
+
+impl Fields {
+    pub fn new() -> Fields {Fields{}}
+}
+
+impl std::fmt::Display for Fields {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Fields>")
+    }
+}
+
+impl Callable for Fields {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let instance = instance_arg("fields", &arguments[0])?;
+        let names = instance.borrow().field_names();
+        Ok(string_list(names))
+    }
+}
+
+#[derive(Debug)]
+pub struct HasField {}
+
+impl HasField {
+    pub fn new() -> HasField {HasField{}}
+}
+
+impl std::fmt::Display for HasField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn HasField>")
+    }
+}
+
+impl Callable for HasField {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let name = field_name_arg("hasField", &arguments[1])?;
+        match &arguments[0] {
+            LoxType::Instance(instance) => Ok(LoxType::Bool(instance.borrow().field(name).is_some())),
+            _ => Ok(LoxType::Bool(false))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GetField {}
+
+impl GetField {
+    pub fn new() -> GetField {GetField{}}
+}
+
+impl std::fmt::Display for GetField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn GetField>")
+    }
+}
+
+impl Callable for GetField {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let instance = instance_arg("getField", &arguments[0])?;
+        let name = field_name_arg("getField", &arguments[1])?;
+        let value = instance.borrow().field(name);
+        value.ok_or_else(|| EvaluationError::NativeError(
+            None,
+            format!("{} has no field {}", arguments[0], name)
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct SetField {}
+
+impl SetField {
+    pub fn new() -> SetField {SetField{}}
+}
+
+impl std::fmt::Display for SetField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn SetField>")
+    }
+}
+
+impl Callable for SetField {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(3)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        let instance = instance_arg("setField", &arguments[0])?;
+        let name = field_name_arg("setField", &arguments[1])?;
+        instance.borrow_mut().set_field(name, arguments[2].clone());
+        Ok(arguments[2].clone())
+    }
+}
+
+#[derive(Debug)]
+pub struct Methods {}
+
+impl Methods {
+    pub fn new() -> Methods {Methods{}}
+}
+
+impl std::fmt::Display for Methods {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn Methods>")
+    }
+}
+
+impl Callable for Methods {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Class(class) => Ok(string_list(class.method_names())),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("methods expects a class, got {}", other)
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SuperclassOf {}
+
+impl SuperclassOf {
+    pub fn new() -> SuperclassOf {SuperclassOf{}}
+}
+
+impl std::fmt::Display for SuperclassOf {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f,"<native_fn SuperclassOf>")
+    }
+}
+
+impl Callable for SuperclassOf {
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, interpreter: &mut crate::interpreter::Interpreter, arguments: &[LoxType]) -> Result<LoxType, EvaluationError> {
+        match &arguments[0] {
+            LoxType::Class(class) => Ok(class.superclass().map_or(LoxType::Nil, LoxType::Class)),
+            other => Err(EvaluationError::NativeError(
+                None,
+                format!("superclassOf expects a class, got {}", other)
+            )),
+        }
+    }
+}
+
+fn instance_arg(native: &str, value: &LoxType) -> Result<Rc<RefCell<LoxInstance>>, EvaluationError> {
+    match value {
+        LoxType::Instance(instance) => Ok(instance.clone()),
+        other => Err(EvaluationError::NativeError(
+            None,
+            format!("{} expects an instance, got {}", native, other)
+        )),
+    }
+}
+
+/// A field name for reflection, which can't reach private fields.
+fn field_name_arg<'a>(native: &str, value: &'a LoxType) -> Result<&'a str, EvaluationError> {
+    match value {
+        LoxType::String(string) if string.starts_with('#') => Err(EvaluationError::NativeError(
+            None,
+            format!("{} can't access private field {}", native, string)
+        )),
+        LoxType::String(string) => Ok(string),
+        other => Err(EvaluationError::NativeError(
+            None,
+            format!("{} expects a field name string, got {}", native, other)
+        )),
+    }
+}
+
+fn string_list(strings: Vec<String>) -> LoxType {
+    LoxType::List(Rc::new(LoxList::new(strings.into_iter().map(LoxType::String).collect())))
+}
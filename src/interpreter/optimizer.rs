@@ -0,0 +1,199 @@
+use super::{Expr, Interpreter, Literal, LoxType, MatchArm, Param, Stmt};
+use super::lox_types::lox_generator::contains_yield;
+use super::token::TokenType;
+
+/// Rewrites a resolved program so it does less work at runtime.
+/// Operators whose operands are all literals are folded into a single literal,
+/// and `if` branches and `while` loops whose conditions are literals are
+/// reduced to the code that would actually run.
+///
+/// Folding evaluates the operator exactly as the interpreter would. An operator
+/// that would raise an error is left alone so the error still happens at runtime,
+/// on the same line.
+pub struct Optimizer {
+    // Evaluates folded operators, never sees anything but literals
+    interpreter: Interpreter,
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer {
+    pub fn new() -> Optimizer {
+        Optimizer {
+            interpreter: Interpreter::new(),
+        }
+    }
+
+    pub fn optimize(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        self.optimize_stmts(stmts)
+    }
+
+    fn optimize_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        stmts
+            .into_iter()
+            .map(|stmt| self.optimize_stmt(stmt))
+            .filter(|stmt| !matches!(stmt, Stmt::Block(stmts) if stmts.is_empty()))
+            .collect()
+    }
+
+    /// Optimizes a statement. A statement that can never do anything becomes an empty block.
+    fn optimize_stmt(&mut self, stmt: Stmt) -> Stmt {
+        match stmt {
+            Stmt::ExprStmt(expr) => Stmt::ExprStmt(self.optimize_expr(expr)),
+            Stmt::PrintStmt(keyword, expr) => Stmt::PrintStmt(keyword, self.optimize_expr(expr)),
+            Stmt::VarDecl(name, value) => Stmt::VarDecl(name, value.map(|value| self.optimize_expr(value))),
+            Stmt::VarDestructure(pattern, value) => Stmt::VarDestructure(pattern, self.optimize_expr(value)),
+            Stmt::AssignDestructure(pattern, value, distances) => {
+                Stmt::AssignDestructure(pattern, self.optimize_expr(value), distances)
+            },
+            Stmt::ConstDecl(name, value) => Stmt::ConstDecl(name, self.optimize_expr(value)),
+            Stmt::Block(stmts) => Stmt::Block(self.optimize_stmts(stmts)),
+            Stmt::If(condition, then, else_stmt) => {
+                let condition = self.optimize_expr(condition);
+                let then = self.optimize_stmt(*then);
+                let else_stmt = else_stmt.map(|else_stmt| self.optimize_stmt(*else_stmt));
+
+                // A branch holding a yield decides whether its function is a generator, so it stays
+                let else_yields = else_stmt.as_ref().is_some_and(contains_yield);
+                match self.literal_value(&condition) {
+                    Some(value) if value.is_truthy() && !else_yields => then,
+                    Some(value) if !value.is_truthy() && !contains_yield(&then) => {
+                        else_stmt.unwrap_or(Stmt::Block(Vec::new()))
+                    },
+                    _ => Stmt::If(condition, Box::new(then), else_stmt.map(Box::new))
+                }
+            },
+            Stmt::While(condition, body) => {
+                let condition = self.optimize_expr(condition);
+                let body = self.optimize_stmt(*body);
+                match self.literal_value(&condition) {
+                    Some(value) if !value.is_truthy() && !contains_yield(&body) => Stmt::Block(Vec::new()),
+                    _ => Stmt::While(condition, Box::new(body))
+                }
+            },
+            Stmt::ForIn(keyword, name, iterable, body) => {
+                Stmt::ForIn(keyword, name, self.optimize_expr(iterable), Box::new(self.optimize_stmt(*body)))
+            },
+            Stmt::Function(name, params, body) => {
+                Stmt::Function(name, self.optimize_params(params), self.optimize_stmts(body))
+            },
+            Stmt::Return(keyword, value) => Stmt::Return(keyword, value.map(|value| self.optimize_expr(value))),
+            Stmt::ClassDecl(name, methods, superclass, traits) => {
+                Stmt::ClassDecl(name, self.optimize_stmts(methods), superclass, traits)
+            },
+            Stmt::TraitDecl(name, methods) => Stmt::TraitDecl(name, self.optimize_stmts(methods)),
+            Stmt::EnumDecl(name, variants) => Stmt::EnumDecl(name, variants),
+            Stmt::Match(keyword, subject, arms) => {
+                let subject = self.optimize_expr(subject);
+                let arms = arms.into_iter().map(|arm| MatchArm {
+                    patterns: arm.patterns,
+                    guard: arm.guard.map(|guard| self.optimize_expr(guard)),
+                    body: Box::new(self.optimize_stmt(*arm.body)),
+                }).collect();
+                Stmt::Match(keyword, subject, arms)
+            },
+            Stmt::Assert(keyword, condition, message, source) => Stmt::Assert(
+                keyword,
+                Box::new(self.optimize_expr(*condition)),
+                message.map(|message| Box::new(self.optimize_expr(*message))),
+                source
+            ),
+            Stmt::Test(keyword, name, body) => Stmt::Test(keyword, name, self.optimize_stmts(body)),
+        }
+    }
+
+    fn optimize_params(&mut self, params: Vec<Param>) -> Vec<Param> {
+        params.into_iter().map(|param| Param {
+            default: param.default.map(|default| self.optimize_expr(default)),
+            ..param
+        }).collect()
+    }
+
+    fn optimize_expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Grouping(inner) => match self.optimize_expr(*inner) {
+                literal @ Expr::Literal(_) => literal,
+                inner => Expr::Grouping(Box::new(inner))
+            },
+            Expr::Unary(operator, right) => {
+                let folded = Expr::Unary(operator, Box::new(self.optimize_expr(*right)));
+                self.fold(folded)
+            },
+            Expr::Binary(left, operator, right) => {
+                let folded = Expr::Binary(
+                    Box::new(self.optimize_expr(*left)),
+                    operator,
+                    Box::new(self.optimize_expr(*right))
+                );
+                self.fold(folded)
+            },
+            Expr::Logical(left, operator, right) => {
+                let left = self.optimize_expr(*left);
+                let right = self.optimize_expr(*right);
+                // The result is one of the operands, so a literal on the left picks it
+                match self.literal_value(&left).map(|value| value.is_truthy()) {
+                    Some(true) if operator.token_type == TokenType::Or => left,
+                    Some(false) if operator.token_type == TokenType::And => left,
+                    Some(_) => right,
+                    None => Expr::Logical(Box::new(left), operator, Box::new(right))
+                }
+            },
+            Expr::Assign(name, value, distance) => Expr::Assign(name, Box::new(self.optimize_expr(*value)), distance),
+            Expr::Call(callee, paren, arguments, named) => Expr::Call(
+                Box::new(self.optimize_expr(*callee)),
+                paren,
+                arguments.into_iter().map(|argument| self.optimize_expr(argument)).collect(),
+                named.into_iter().map(|(name, argument)| (name, self.optimize_expr(argument))).collect()
+            ),
+            Expr::Get(object, name) => Expr::Get(Box::new(self.optimize_expr(*object)), name),
+            Expr::Set(object, name, value) => {
+                Expr::Set(Box::new(self.optimize_expr(*object)), name, Box::new(self.optimize_expr(*value)))
+            },
+            Expr::Yield(keyword, value) => Expr::Yield(keyword, value.map(|value| Box::new(self.optimize_expr(*value)))),
+            expr @ (Expr::Literal(_) | Expr::Var(_, _) | Expr::This(_, _) | Expr::Super(_, _, _)) => expr,
+        }
+    }
+
+    /// The value of an expression that is a literal.
+    fn literal_value(&self, expr: &Expr) -> Option<LoxType> {
+        match expr {
+            Expr::Literal(literal) => self.interpreter.evaluate_literal_expr(literal).ok(),
+            _ => None
+        }
+    }
+
+    /// Replaces an operator whose operands are literals with its result,
+    /// unless evaluating it fails or gives something that isn't a literal.
+    fn fold(&mut self, expr: Expr) -> Expr {
+        let operands_are_literals = match &expr {
+            Expr::Unary(_, right) => self.literal_value(right).is_some(),
+            Expr::Binary(left, _, right) => self.literal_value(left).is_some() && self.literal_value(right).is_some(),
+            _ => false
+        };
+        if !operands_are_literals {
+            return expr;
+        }
+
+        match self.interpreter.evaluate_expr(&expr).ok().and_then(|value| to_literal(&value)) {
+            Some(literal) => Expr::Literal(literal),
+            None => expr
+        }
+    }
+}
+
+fn to_literal(value: &LoxType) -> Option<Literal> {
+    match value {
+        LoxType::Nil => Some(Literal::Nil),
+        LoxType::Bool(b) => Some(Literal::Bool(*b)),
+        LoxType::Int(n) => Some(Literal::Int(*n)),
+        LoxType::Num(n) => Some(Literal::Num(*n)),
+        LoxType::BigInt(n) => Some(Literal::BigInt(n.clone())),
+        LoxType::Decimal(n) => Some(Literal::Decimal(n.clone())),
+        LoxType::String(s) => Some(Literal::String(s.clone())),
+        _ => None
+    }
+}
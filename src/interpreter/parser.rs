@@ -1,9 +1,12 @@
 use super::token::{Token,TokenType};
 use super::Literal;
 use super::Expr;
-use super::Stmt;
+use super::{Stmt,Param};
+use super::{Pattern,MatchArm,Destructure};
 use super::lox_types::FuncType;
 
+use std::rc::Rc;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
@@ -42,6 +45,17 @@ impl std::fmt::Display for ParsingError {
     }
 }
 
+impl ParsingError {
+    /// The token the error was found at, if the error has one.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            ParsingError::UnexpectedTokenError(token, _) |
+            ParsingError::InvalidAssignmentError(token) => Some(token),
+            _ => None
+        }
+    }
+}
+
 impl std::error::Error for ParsingError {
     fn description(&self) -> &str {
         match *self {
@@ -71,6 +85,15 @@ impl Parser {
         Ok(statements)
     }
 
+    /// Parses tokens holding a single expression, such as one typed in the debugger.
+    pub fn parse_expression(&mut self) -> ParseResult<Expr> {
+        let expr = self.expression()?;
+        if !self.is_at_end() {
+            return Err(ParsingError::UnexpectedTokenError(self.peek().clone(), "Expect end of expression at".to_string()));
+        }
+        Ok(expr)
+    }
+
     fn declaration(&mut self) -> ParseResult<Stmt> {
         let peek = self.peek();
         let result = match peek.token_type {
@@ -78,14 +101,31 @@ impl Parser {
                 self.advance();
                 self.var_declaration()
             },
+            TokenType::Const => {
+                self.advance();
+                self.const_declaration()
+            },
             TokenType::Class => {
                 self.advance();
                 self.class_declaration()
             }
+            TokenType::Trait => {
+                self.advance();
+                self.trait_declaration()
+            }
+            TokenType::Enum => {
+                self.advance();
+                self.enum_declaration()
+            }
             TokenType::Fun => {
                 self.advance();
                 self.function(FuncType::Function)
             }
+            // `test` is only a keyword when followed by the test's name
+            TokenType::Identifier if peek.lexeme == "test" && self.check_ahead(1, TokenType::String) => {
+                self.advance();
+                self.test_declaration()
+            }
             _ => self.statement()
         };
 
@@ -99,6 +139,14 @@ impl Parser {
     }
 
     fn var_declaration(&mut self) -> ParseResult<Stmt> {
+        if self.match_token(&[TokenType::LeftParen, TokenType::LeftBrace]) {
+            let pattern = self.destructure()?;
+            self.consume(TokenType::Equal, "Expect '=' after destructuring pattern.")?;
+            let initializer = self.expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+            return Ok(Stmt::VarDestructure(pattern, initializer));
+        }
+
         let name = self.consume(TokenType::Identifier, "Expect variable name.")?.clone();
         let mut initializer = None;
 
@@ -111,6 +159,66 @@ impl Parser {
         Ok(Stmt::VarDecl(name, initializer))
     }
 
+    /// Parses the names of a destructuring pattern after its opening '(' or '{'.
+    fn destructure(&mut self) -> ParseResult<Destructure> {
+        let open = self.previous().clone();
+        let close = match open.token_type {
+            TokenType::LeftParen => TokenType::RightParen,
+            _ => TokenType::RightBrace,
+        };
+
+        let mut names: Vec<Token> = Vec::new();
+        loop {
+            let name = self.consume(TokenType::Identifier, "Expect name in destructuring pattern.")?.clone();
+            if name.lexeme != "_" && names.iter().any(|other| other.lexeme == name.lexeme) {
+                return Err(ParsingError::UnexpectedTokenError(name, "Duplicate name in destructuring pattern:".to_string()))
+            }
+            names.push(name);
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(close, "Expect closing bracket after destructuring pattern.")?;
+
+        match close {
+            TokenType::RightParen => Ok(Destructure::Tuple(open, names)),
+            _ => Ok(Destructure::Fields(open, names)),
+        }
+    }
+
+    /// Looks ahead for `(a, b) =` or `{x, y} =` at the start of a statement.
+    /// A single parenthesized name stays a (invalid) grouping assignment.
+    fn check_destructuring_assignment(&self) -> bool {
+        let close = match self.peek().token_type {
+            TokenType::LeftParen => TokenType::RightParen,
+            TokenType::LeftBrace => TokenType::RightBrace,
+            _ => return false,
+        };
+
+        let mut n = 1;
+        loop {
+            if !self.check_ahead(n, TokenType::Identifier) {
+                return false;
+            }
+            n += 1;
+            if !self.check_ahead(n, TokenType::Comma) {
+                break;
+            }
+            n += 1;
+        }
+        let names = n / 2;
+        let enough = close == TokenType::RightBrace || names > 1;
+        enough && self.check_ahead(n, close) && self.check_ahead(n + 1, TokenType::Equal)
+    }
+
+    fn const_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expect constant name.")?.clone();
+        self.consume(TokenType::Equal, "Expect '=' after constant name, constants must be initialized.")?;
+        let initializer = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after constant declaration.")?;
+        Ok(Stmt::ConstDecl(name, initializer))
+    }
+
     fn class_declaration(&mut self) -> ParseResult<Stmt> {
         let name = self.consume(TokenType::Identifier, "Expect class name.")?.clone();
         
@@ -120,6 +228,17 @@ impl Parser {
             superclass = Some(Expr::Var(self.previous().clone(), None));
         }
 
+        let mut traits = Vec::new();
+        if self.match_token(&[TokenType::With]) {
+            loop {
+                self.consume(TokenType::Identifier, "Expect trait name.")?;
+                traits.push(Expr::Var(self.previous().clone(), None));
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
         self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
 
         let mut methods = Vec::new();
@@ -128,12 +247,66 @@ impl Parser {
         }
 
         self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
-        Ok(Stmt::ClassDecl(name, methods, superclass))
+        Ok(Stmt::ClassDecl(name, methods, superclass, traits))
+    }
+
+    fn trait_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expect trait name.")?.clone();
+        self.consume(TokenType::LeftBrace, "Expect '{' before trait body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function(FuncType::Method)?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after trait body.")?;
+        Ok(Stmt::TraitDecl(name, methods))
+    }
+
+    fn enum_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expect enum name.")?.clone();
+        self.consume(TokenType::LeftBrace, "Expect '{' before enum body.")?;
+
+        let mut variants: Vec<(Token, Vec<Token>)> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let variant = self.consume(TokenType::Identifier, "Expect variant name.")?.clone();
+            if variants.iter().any(|(other, _)| other.lexeme == variant.lexeme) {
+                return Err(ParsingError::UnexpectedTokenError(variant, "Duplicate variant name:".to_string()))
+            }
+
+            let mut fields: Vec<Token> = Vec::new();
+            if self.match_token(&[TokenType::LeftParen]) {
+                loop {
+                    let field = self.consume(TokenType::Identifier, "Expect field name.")?.clone();
+                    if fields.iter().any(|other| other.lexeme == field.lexeme) {
+                        return Err(ParsingError::UnexpectedTokenError(field, "Duplicate field name:".to_string()))
+                    }
+                    fields.push(field);
+                    if !self.match_token(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightParen, "Expect ')' after variant fields.")?;
+            }
+            variants.push((variant, fields));
+
+            if !self.match_token(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after enum body.")?;
+        Ok(Stmt::EnumDecl(name, variants))
     }
 
     
     fn function(&mut self, func_type: FuncType) -> ParseResult<Stmt> {
-        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", func_type))?.clone();
+        // Methods named #name are private to their class
+        let name = if matches!(func_type, FuncType::Method) && self.match_token(&[TokenType::PrivateIdentifier]) {
+            self.previous().clone()
+        } else {
+            self.consume(TokenType::Identifier, &format!("Expect {} name.", func_type))?.clone()
+        };
         self.consume(TokenType::LeftParen, &format!("Expect '(' after {} name.", func_type))?;
 
         let mut parameters = Vec::new();
@@ -142,8 +315,15 @@ impl Parser {
                 if parameters.len() >= 255 {
                     return Err(ParsingError::TooManyArgumentsError)
                 }
-                let param = self.consume(TokenType::Identifier, "Expect parameter name.")?.clone();
+                let param = self.parameter(&parameters)?;
+                let rest = param.rest;
                 parameters.push(param);
+                if rest && self.check(TokenType::Comma) {
+                    return Err(ParsingError::UnexpectedTokenError(
+                        self.peek().clone(),
+                        "A rest parameter must be the last parameter, found".to_string()
+                    ))
+                }
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
                 }
@@ -157,8 +337,40 @@ impl Parser {
         Ok(Stmt::Function(name.clone(), parameters, body))
     }
 
+    fn parameter(&mut self, previous: &[Param]) -> ParseResult<Param> {
+        let rest = self.match_token(&[TokenType::Ellipsis]);
+        let name = self.consume(TokenType::Identifier, "Expect parameter name.")?.clone();
+
+        let mut default = None;
+        if self.match_token(&[TokenType::Equal]) {
+            if rest {
+                return Err(ParsingError::UnexpectedTokenError(
+                    name,
+                    "A rest parameter can't have a default value:".to_string()
+                ))
+            }
+            default = Some(self.expression()?);
+        } else if !rest && previous.iter().any(|param| param.default.is_some()) {
+            return Err(ParsingError::UnexpectedTokenError(
+                name,
+                "Expect a default value for parameters following one with a default:".to_string()
+            ))
+        }
+
+        Ok(Param { name, default, rest })
+    }
+
 
     fn statement(&mut self) -> ParseResult<Stmt> {
+        if self.check_destructuring_assignment() {
+            self.advance();
+            let pattern = self.destructure()?;
+            self.consume(TokenType::Equal, "Expect '=' after destructuring pattern.")?;
+            let value = self.expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after expression")?;
+            return Ok(Stmt::AssignDestructure(pattern, value, Vec::new()));
+        }
+
         let peek = self.peek();
         match peek.token_type {
             TokenType::For => {
@@ -173,6 +385,10 @@ impl Parser {
                 self.advance();
                 self.print_statement()
             },
+            TokenType::Assert => {
+                self.advance();
+                self.assert_statement()
+            },
             TokenType::Return => {
                 self.advance();
                 self.return_statement()
@@ -181,6 +397,10 @@ impl Parser {
                 self.advance();
                 self.while_statement()
             }
+            TokenType::Match => {
+                self.advance();
+                self.match_statement()
+            }
             TokenType::LeftBrace => {
                 self.advance();
                 Ok(Stmt::Block(self.block_statement()?))
@@ -190,8 +410,18 @@ impl Parser {
     }
 
     fn for_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
+        let is_for_in = if self.check(TokenType::Var) {
+            self.check_ahead(1, TokenType::Identifier) && self.check_ahead(2, TokenType::In)
+        } else {
+            self.check(TokenType::Identifier) && self.check_ahead(1, TokenType::In)
+        };
+        if is_for_in {
+            return self.for_in_statement(keyword);
+        }
+
         let mut initializer: Option<Stmt> = None;
         if self.match_token(&[TokenType::Semicolon]) {}
         else if self.match_token(&[TokenType::Var]) {
@@ -237,6 +467,17 @@ impl Parser {
         Ok(body)
     }
 
+    fn for_in_statement(&mut self, keyword: Token) -> ParseResult<Stmt> {
+        self.match_token(&[TokenType::Var]);
+        let name = self.consume(TokenType::Identifier, "Expect loop variable name.")?.clone();
+        self.consume(TokenType::In, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for-in clause.")?;
+
+        let body = self.statement()?;
+        Ok(Stmt::ForIn(keyword, name, iterable, Box::new(body)))
+    }
+
     fn if_statement(&mut self) -> ParseResult<Stmt> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
         let condition = self.expression()?;
@@ -253,9 +494,34 @@ impl Parser {
     }
 
     fn print_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
         let value = self.expression()?;
         self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
-        Ok(Stmt::PrintStmt(value))
+        Ok(Stmt::PrintStmt(keyword, value))
+    }
+
+    fn assert_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        let start = self.current;
+        let condition = self.expression()?;
+        let source = source_text(&self.tokens[start..self.current]);
+
+        let mut message = None;
+        if self.match_token(&[TokenType::Comma]) {
+            message = Some(Box::new(self.expression()?));
+        }
+        self.consume(TokenType::Semicolon, "Expect ';' after assertion.")?;
+        Ok(Stmt::Assert(keyword, Box::new(condition), message, source))
+    }
+
+    fn test_declaration(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        let name = match self.advance().literal {
+            Some(Literal::String(ref name)) => name.clone(),
+            _ => unreachable!("test names are string tokens")
+        };
+        self.consume(TokenType::LeftBrace, "Expect '{' before test body.")?;
+        Ok(Stmt::Test(keyword, name, self.block_statement()?))
     }
 
     fn return_statement(&mut self) -> ParseResult<Stmt> {
@@ -279,6 +545,112 @@ impl Parser {
         Ok(Stmt::While(condition, Box::new(body)))
     }
 
+    fn match_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous().clone();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'match'.")?;
+        let subject = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after match subject.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before match arms.")?;
+
+        let mut arms = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            self.consume(TokenType::Case, "Expect 'case' before match arm.")?;
+
+            let mut patterns = vec![self.pattern()?];
+            while self.match_token(&[TokenType::Comma]) {
+                patterns.push(self.pattern()?);
+            }
+
+            let mut guard = None;
+            if self.match_token(&[TokenType::If]) {
+                guard = Some(self.expression()?);
+            }
+
+            self.consume(TokenType::FatArrow, "Expect '=>' after match pattern.")?;
+            let body = self.statement()?;
+            arms.push(MatchArm {
+                patterns,
+                guard,
+                body: Box::new(body),
+            });
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after match arms.")?;
+        Ok(Stmt::Match(keyword, subject, arms))
+    }
+
+    fn pattern(&mut self) -> ParseResult<Pattern> {
+        if self.match_token(&[
+            TokenType::Number,
+            TokenType::String,
+            TokenType::False,
+            TokenType::True,
+            TokenType::Nil,
+        ]) {
+            let token = self.previous().clone();
+            return match token.literal {
+                Some(ref literal) => Ok(Pattern::Literal(token.clone(), literal.clone())),
+                None => Err(ParsingError::InternalError(
+                    "Undefined Literal".to_string()
+                ))
+            }
+        }
+
+        if self.match_token(&[TokenType::Minus]) {
+            let token = self.consume(TokenType::Number, "Expect number after '-' in pattern.")?.clone();
+            let literal = match token.literal {
+                Some(Literal::Int(n)) => Literal::Int(-n),
+                Some(Literal::Num(n)) => Literal::Num(-n),
+                Some(Literal::BigInt(ref n)) => Literal::BigInt(Rc::new(-&**n)),
+                Some(Literal::Decimal(ref n)) => Literal::Decimal(Rc::new(-&**n)),
+                _ => return Err(ParsingError::InternalError(
+                    "Undefined Literal".to_string()
+                ))
+            };
+            return Ok(Pattern::Literal(token, literal));
+        }
+
+        if self.match_token(&[TokenType::Identifier]) {
+            let name = self.previous().clone();
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard(name));
+            }
+
+            if self.match_token(&[TokenType::Dot]) {
+                let variant = self.consume(TokenType::Identifier, "Expect variant name after '.' in pattern.")?.clone();
+                let mut fields = None;
+                if self.match_token(&[TokenType::LeftParen]) {
+                    fields = Some(self.pattern_fields("Expect binding name in variant pattern.")?);
+                }
+                return Ok(Pattern::Variant(name.clone(), variant, Expr::Var(name, None), fields));
+            }
+
+            if self.match_token(&[TokenType::LeftParen]) {
+                let fields = self.pattern_fields("Expect field name in class pattern.")?;
+                return Ok(Pattern::Class(name.clone(), Expr::Var(name, None), fields));
+            }
+
+            return Ok(Pattern::Binding(name));
+        }
+
+        Err(ParsingError::UnexpectedTokenError(self.peek().clone(), "Expect pattern.".to_string()))
+    }
+
+    fn pattern_fields(&mut self, msg: &str) -> ParseResult<Vec<Token>> {
+        let mut fields = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                let field = self.consume(TokenType::Identifier, msg)?;
+                fields.push(field.clone());
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after pattern fields.")?;
+        Ok(fields)
+    }
+
     fn block_statement(&mut self) -> ParseResult<Vec<Stmt>> {
         let mut statements = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
@@ -301,6 +673,10 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> ParseResult<Expr> {
+        if self.match_token(&[TokenType::Yield]) {
+            return self.yield_expression();
+        }
+
         let expr = self.or()?;
 
         if self.match_token(&[TokenType::Equal]) {
@@ -319,6 +695,17 @@ impl Parser {
         Ok(expr)
     }
 
+    fn yield_expression(&mut self) -> ParseResult<Expr> {
+        let keyword = self.previous().clone();
+        let mut value: Option<Box<Expr>> = None;
+
+        if !self.check(TokenType::Semicolon) && !self.check(TokenType::RightParen) {
+            value = Some(Box::new(self.assignment()?));
+        }
+
+        Ok(Expr::Yield(keyword, value))
+    }
+
     fn or(&mut self) -> ParseResult<Expr> {
         let mut expr = self.and()?;
 
@@ -367,7 +754,8 @@ impl Parser {
             TokenType::Greater,
             TokenType::GreaterEqual,
             TokenType::Less,
-            TokenType::LessEqual
+            TokenType::LessEqual,
+            TokenType::Is
         ];
 
         while self.match_token(&matches) {
@@ -402,6 +790,8 @@ impl Parser {
         let matches = vec![
             TokenType::Slash,
             TokenType::Star,
+            TokenType::Percent,
+            TokenType::TildeSlash,
         ];
 
         while self.match_token(&matches) {
@@ -436,7 +826,11 @@ impl Parser {
             if self.match_token(&[TokenType::LeftParen]) {
                 expr = self.finish_call(expr)?;
             } else if self.match_token(&[TokenType::Dot]) {
-                let name = self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                let name = if self.match_token(&[TokenType::PrivateIdentifier]) {
+                    self.previous()
+                } else {
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?
+                };
                 expr = Expr::Get(Box::new(expr), name.clone());
             } else {
                 break;
@@ -449,13 +843,31 @@ impl Parser {
 
     fn finish_call(&mut self, callee: Expr) -> ParseResult<Expr> {
         let mut arguments = Vec::new();
+        let mut named: Vec<(Token, Expr)> = Vec::new();
 
         if !self.check(TokenType::RightParen) {
             loop {
-                if arguments.len() >= 255 {
+                if arguments.len() + named.len() >= 255 {
                     return Err(ParsingError::TooManyArgumentsError)
                 }
-                arguments.push(self.expression()?);
+                if self.check(TokenType::Identifier) && self.check_ahead(1, TokenType::Colon) {
+                    let name = self.advance().clone();
+                    self.advance();
+                    if named.iter().any(|(other, _)| other.lexeme == name.lexeme) {
+                        return Err(ParsingError::UnexpectedTokenError(
+                            name,
+                            "Duplicate named argument:".to_string()
+                        ))
+                    }
+                    named.push((name, self.expression()?));
+                } else if !named.is_empty() {
+                    return Err(ParsingError::UnexpectedTokenError(
+                        self.peek().clone(),
+                        "Positional arguments can't follow named arguments, found".to_string()
+                    ))
+                } else {
+                    arguments.push(self.expression()?);
+                }
                 if !self.match_token(&[TokenType::Comma]) {
                     break;
                 }
@@ -467,7 +879,7 @@ impl Parser {
             "Expect ')' afte rarguments."
         )?;
 
-        Ok(Expr::Call(Box::new(callee), paren.clone(), arguments))
+        Ok(Expr::Call(Box::new(callee), paren.clone(), arguments, named))
     }
 
     fn primary(&mut self) -> ParseResult<Expr> {
@@ -536,6 +948,14 @@ impl Parser {
         }
     }
 
+    /// Checks the type of the token `n` tokens past the current one.
+    fn check_ahead(&self, n: usize, token_type: TokenType) -> bool {
+        match self.tokens.get(self.current + n) {
+            Some(token) => token.token_type == token_type,
+            None => false
+        }
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -576,9 +996,9 @@ impl Parser {
             }
 
             match self.peek().token_type {
-                TokenType::Class | TokenType::Fun | TokenType::Var | 
+                TokenType::Class | TokenType::Trait | TokenType::Enum | TokenType::Fun | TokenType::Var | TokenType::Const |
                 TokenType::For | TokenType::If | TokenType::While | 
-                TokenType::Print | TokenType::Return => return,
+                TokenType::Print | TokenType::Return | TokenType::Match => return,
                 _ => ()
             };
 
@@ -589,3 +1009,35 @@ impl Parser {
 
 
 }
+
+/// Rebuilds readable source for an expression from its tokens, spacing
+/// operators but not calls, property accesses or unary operators.
+fn source_text(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&Token> = None;
+    let mut after_operand = false;
+    let mut after_unary = false;
+    for token in tokens {
+        if let Some(previous) = previous {
+            let call = token.token_type == TokenType::LeftParen && after_operand;
+            let tight = after_unary
+                || matches!(previous.token_type, TokenType::LeftParen | TokenType::Dot)
+                || matches!(token.token_type,
+                    TokenType::RightParen | TokenType::Comma | TokenType::Dot | TokenType::Colon
+                );
+            if !call && !tight {
+                text.push(' ');
+            }
+        }
+        text.push_str(&token.lexeme);
+
+        // A minus or bang is unary unless it follows an operand
+        after_unary = matches!(token.token_type, TokenType::Minus | TokenType::Bang) && !after_operand;
+        after_operand = matches!(token.token_type,
+            TokenType::Identifier | TokenType::PrivateIdentifier | TokenType::String | TokenType::Number |
+            TokenType::True | TokenType::False | TokenType::Nil | TokenType::This | TokenType::RightParen
+        );
+        previous = Some(token);
+    }
+    text
+}
@@ -0,0 +1,105 @@
+use super::{Token,Expr,Stmt,Literal};
+
+#[derive(Debug,Clone)]
+pub enum Pattern {
+    Literal(Token, Literal),
+    Wildcard(Token),
+    Binding(Token),
+    Class(Token, Expr, Vec<Token>),
+    // Enum name, variant name, the enum expression and the payload bindings, if any
+    Variant(Token, Token, Expr, Option<Vec<Token>>),
+}
+
+impl Pattern {
+    pub fn token(&self) -> &Token {
+        match *self {
+            Pattern::Literal(ref token, _) |
+            Pattern::Wildcard(ref token) |
+            Pattern::Binding(ref token) |
+            Pattern::Class(ref token, _, _) |
+            Pattern::Variant(_, ref token, _, _) => token,
+        }
+    }
+
+    /// Names bound by this pattern when it matches, in declaration order.
+    pub fn bindings(&self) -> Vec<&Token> {
+        match *self {
+            Pattern::Binding(ref name) => vec![name],
+            Pattern::Class(_, _, ref fields) |
+            Pattern::Variant(_, _, _, Some(ref fields)) => fields
+                .iter()
+                .filter(|field| field.lexeme != "_")
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Pattern::Literal(_, ref literal) => write!(f, "{}", literal),
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::Binding(ref name) => write!(f, "{}", name.lexeme),
+            Pattern::Class(ref name, _, ref fields) => {
+                let fields: Vec<&str> = fields.iter().map(|field| field.lexeme.as_str()).collect();
+                write!(f, "{}({})", name.lexeme, fields.join(", "))
+            }
+            Pattern::Variant(ref name, ref variant, _, None) => write!(f, "{}.{}", name.lexeme, variant.lexeme),
+            Pattern::Variant(ref name, ref variant, _, Some(ref fields)) => {
+                let fields: Vec<&str> = fields.iter().map(|field| field.lexeme.as_str()).collect();
+                write!(f, "{}.{}({})", name.lexeme, variant.lexeme, fields.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug,Clone)]
+pub struct MatchArm {
+    pub patterns: Vec<Pattern>,
+    pub guard: Option<Expr>,
+    pub body: Box<Stmt>,
+}
+
+/// The left-hand side of a destructuring declaration or assignment.
+/// `(a, b)` takes the items of an iterable in order and `{x, y}` reads fields by name.
+#[derive(Debug,Clone)]
+pub enum Destructure {
+    Tuple(Token, Vec<Token>),
+    Fields(Token, Vec<Token>),
+}
+
+impl Destructure {
+    pub fn token(&self) -> &Token {
+        match *self {
+            Destructure::Tuple(ref token, _) |
+            Destructure::Fields(ref token, _) => token,
+        }
+    }
+
+    /// Names bound by this pattern, skipping `_` placeholders.
+    pub fn bindings(&self) -> Vec<&Token> {
+        match *self {
+            Destructure::Tuple(_, ref names) |
+            Destructure::Fields(_, ref names) => names
+                .iter()
+                .filter(|name| name.lexeme != "_")
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Destructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Destructure::Tuple(_, ref names) => {
+                let names: Vec<&str> = names.iter().map(|name| name.lexeme.as_str()).collect();
+                write!(f, "({})", names.join(", "))
+            }
+            Destructure::Fields(_, ref names) => {
+                let names: Vec<&str> = names.iter().map(|name| name.lexeme.as_str()).collect();
+                write!(f, "{{{}}}", names.join(", "))
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::{
     Stmt,
     Expr,
     Token,
+    Pattern,
+    Param,
 };
+use super::token::TokenType;
 
 type ResolveResult<T> = Result<T, ResolvingError>;
 
@@ -15,6 +18,12 @@ pub enum ResolvingError {
     ReturnOutOfFunc(Token, String),
     ThisOutOfClass(Token, String),
     ReturnInInit(Token, String),
+    InvalidPattern(Token, String),
+    AssignToConstant(Token, String),
+    InvalidYield(Token, String),
+    PrivateAccess(Token, String),
+    UndefinedGlobal(Token, String),
+    InvalidTest(Token, String),
 }
 
 impl std::fmt::Display for ResolvingError {
@@ -50,6 +59,60 @@ impl std::fmt::Display for ResolvingError {
                 message,
                 token.lexeme)
             },
+            ResolvingError::InvalidPattern(ref token, ref message) => {
+                write!(f, "[line {}] InvalidPattern: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+            ResolvingError::AssignToConstant(ref token, ref message) => {
+                write!(f, "[line {}] AssignToConstant: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+            ResolvingError::InvalidYield(ref token, ref message) => {
+                write!(f, "[line {}] InvalidYield: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+            ResolvingError::PrivateAccess(ref token, ref message) => {
+                write!(f, "[line {}] PrivateAccess: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+            ResolvingError::UndefinedGlobal(ref token, ref message) => {
+                write!(f, "[line {}] UndefinedGlobal: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+            ResolvingError::InvalidTest(ref token, ref message) => {
+                write!(f, "[line {}] InvalidTest: {}: {}",
+                token.line,
+                message,
+                token.lexeme)
+            },
+        }
+    }
+}
+
+impl ResolvingError {
+    pub fn token(&self) -> &Token {
+        match self {
+            ResolvingError::ReferencedInInitializer(token, _) |
+            ResolvingError::AlreadyExists(token, _) |
+            ResolvingError::ReturnOutOfFunc(token, _) |
+            ResolvingError::ThisOutOfClass(token, _) |
+            ResolvingError::ReturnInInit(token, _) |
+            ResolvingError::InvalidPattern(token, _) |
+            ResolvingError::AssignToConstant(token, _) |
+            ResolvingError::InvalidYield(token, _) |
+            ResolvingError::PrivateAccess(token, _) |
+            ResolvingError::UndefinedGlobal(token, _) |
+            ResolvingError::InvalidTest(token, _) => token
         }
     }
 }
@@ -62,6 +125,12 @@ impl std::error::Error for ResolvingError {
             ResolvingError::ReturnOutOfFunc(_, _) => "ReturnOutOfFunc",
             ResolvingError::ThisOutOfClass(_, _) => "ThisOutOfClass",
             ResolvingError::ReturnInInit(_, _) => "ReturnInInit",
+            ResolvingError::InvalidPattern(_, _) => "InvalidPattern",
+            ResolvingError::AssignToConstant(_, _) => "AssignToConstant",
+            ResolvingError::InvalidYield(_, _) => "InvalidYield",
+            ResolvingError::PrivateAccess(_, _) => "PrivateAccess",
+            ResolvingError::UndefinedGlobal(_, _) => "UndefinedGlobal",
+            ResolvingError::InvalidTest(_, _) => "InvalidTest",
         }
     }
 }
@@ -78,13 +147,109 @@ enum FuncType {
 enum ClassType {
     None,
     Class,
-    Subclass
+    Subclass,
+    Trait,
+}
+
+/// A problem the resolver reports without stopping the program from running.
+#[derive(Debug)]
+pub struct ResolvingWarning {
+    pub token: Token,
+    pub message: String,
+}
+
+impl std::fmt::Display for ResolvingWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[line {}] Warning: {}: {}",
+        self.token.line,
+        self.message,
+        self.token.lexeme)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+enum VarKind {
+    Variable,
+    Parameter,
+    // Functions, classes, traits and enums, which aren't reported when unused
+    Declaration,
+    Keyword,
+}
+
+#[derive(Debug, Clone)]
+struct Variable {
+    defined: bool,
+    constant: bool,
+    used: bool,
+    kind: VarKind,
+    // Where the name was declared, or None for `this` and `super`
+    token: Option<Token>,
+    // Index of the name's entry in the resolver's declarations
+    declaration: Option<usize>,
+}
+
+/// What a declared name is, as shown by tools such as the language server.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum DeclarationKind {
+    Variable,
+    Constant,
+    Parameter,
+    Function,
+    Class,
+    Trait,
+    Enum,
+}
+
+impl std::fmt::Display for DeclarationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            DeclarationKind::Variable => "variable",
+            DeclarationKind::Constant => "constant",
+            DeclarationKind::Parameter => "parameter",
+            DeclarationKind::Function => "function",
+            DeclarationKind::Class => "class",
+            DeclarationKind::Trait => "trait",
+            DeclarationKind::Enum => "enum",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A name declared in the resolved code.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub token: Token,
+    pub kind: DeclarationKind,
+    // Whether the name was declared at the top level
+    pub global: bool,
+}
+
+/// A use of a name, with the index of the declaration it refers to.
+/// Globals that aren't declared in the resolved code, such as natives, have none.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub token: Token,
+    pub declaration: Option<usize>,
 }
 
 pub struct Resolver{
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, Variable>>,
     current_function: FuncType,
     current_class: ClassType,
+    // Set when the expression being resolved is in a position where a generator can suspend
+    yield_allowed: bool,
+    // Set inside a test block, which runs after the rest of the program
+    in_test: bool,
+    warnings: Vec<ResolvingWarning>,
+    // Names declared at the top level, and references that didn't resolve
+    // to a local, for the strict check of globals.  Each reference comes with
+    // whether it's deferred, in a function or test that may run once the
+    // whole program is declared, rather than running as soon as it's reached
+    global_declarations: HashSet<String>,
+    global_references: Vec<(Token, bool)>,
+    errors: Vec<ResolvingError>,
+    declarations: Vec<Declaration>,
+    references: Vec<Reference>,
 }
 
 impl Resolver {
@@ -92,36 +257,158 @@ impl Resolver {
         Resolver { 
             scopes: Vec::new(),
             current_function: FuncType::None,
-            current_class: ClassType::None
+            current_class: ClassType::None,
+            yield_allowed: false,
+            in_test: false,
+            warnings: Vec::new(),
+            global_declarations: HashSet::new(),
+            global_references: Vec::new(),
+            errors: Vec::new(),
+            declarations: Vec::new(),
+            references: Vec::new(),
         }
     }
 
-    pub fn resolve_ast(&mut self, ast: &mut [Stmt]) -> ResolveResult<()> {
-        for stmt in ast.iter_mut() {
-            self.resolve_stmt(stmt)?;
+    /// Reports every reference to a global that is neither declared at the top
+    /// level of the resolved code nor in `known`, which holds the globals
+    /// already defined such as natives.  Functions may refer to globals
+    /// declared after them, so this runs once the whole program is resolved,
+    /// but code outside of functions must come after the declarations it uses.
+    pub fn check_globals(&self, known: &HashSet<String>) -> Vec<ResolvingError> {
+        self.global_references
+            .iter()
+            .filter(|(name, deferred)| {
+                let declared = *deferred && self.global_declarations.contains(&name.lexeme);
+                !declared && !known.contains(&name.lexeme)
+            })
+            .map(|(name, _)| ResolvingError::UndefinedGlobal(
+                name.clone(),
+                "Undefined variable".to_string()
+            ))
+            .collect()
+    }
+
+    pub fn warnings(&self) -> &[ResolvingWarning] {
+        &self.warnings
+    }
+
+    /// Every name declared in the resolved code, in the order they were resolved.
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    /// Every use of a variable in the resolved code.
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// Resolves a program, returning every error found in it.
+    pub fn resolve_ast(&mut self, ast: &mut [Stmt]) -> Result<(), Vec<ResolvingError>> {
+        self.resolve_stmts(ast);
+
+        // Globals can be used before they're declared, so they're linked by name at the end
+        for reference in self.references.iter_mut().filter(|reference| reference.declaration.is_none()) {
+            reference.declaration = self.declarations
+                .iter()
+                .position(|declaration| declaration.global && declaration.token.lexeme == reference.token.lexeme);
+        }
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Resolves a list of statements.  A statement with an error is abandoned
+    /// and the error recorded, and resolving carries on with the next one.
+    fn resolve_stmts(&mut self, ast: &mut [Stmt]) {
+        for i in 0..ast.len() {
+            let stmt = &mut ast[i];
+            let depth = self.scopes.len();
+            let (enclosing_function, enclosing_class) = (self.current_function, self.current_class);
+            if let Err(e) = self.resolve_stmt(stmt) {
+                self.errors.push(e);
+                self.scopes.truncate(depth);
+                self.current_function = enclosing_function;
+                self.current_class = enclosing_class;
+                self.yield_allowed = false;
+            }
+            if let (Stmt::Return(keyword, _), Some(dead)) = (&ast[i], ast.get(i + 1)) {
+                // Point at the first statement that can't run, or the return for a
+                // statement without a token of its own
+                let token = first_token(dead).unwrap_or(keyword).clone();
+                self.warn(&token, "Unreachable code after return");
+            }
         };
-        Ok(())
     }
 
 
     fn resolve_stmt(&mut self, stmt: &mut Stmt) -> ResolveResult<()> {
         match stmt {
             Stmt::ExprStmt(expr) => {
+                self.yield_allowed = match expr {
+                    Expr::Yield(_, _) => true,
+                    Expr::Assign(_, value, _) => matches!(**value, Expr::Yield(_, _)),
+                    _ => false
+                };
                 self.resolve_expr(expr)?;
+                self.yield_allowed = false;
             },
-            Stmt::PrintStmt(expr) => {
+            Stmt::PrintStmt(_, expr) => {
                 self.resolve_expr(expr)?;
             },
+            Stmt::Assert(_, condition, message, _) => {
+                self.resolve_expr(condition)?;
+                if let Some(message) = message {
+                    self.resolve_expr(message)?;
+                }
+            },
+            Stmt::Test(keyword, _, body) => {
+                if !self.scopes.is_empty() {
+                    return Err(ResolvingError::InvalidTest(
+                        keyword.clone(),
+                        "Test blocks must be at the top level".to_string()))
+                }
+                self.begin_scope();
+                self.in_test = true;
+                self.resolve_stmts(body);
+                self.in_test = false;
+                self.end_scope();
+            },
             Stmt::VarDecl(name, initializer) => {
-                self.declare(&name)?;
+                self.declare(&name, DeclarationKind::Variable)?;
                 if let Some(initializer) = initializer {
+                    self.yield_allowed = matches!(initializer, Expr::Yield(_, _));
                     self.resolve_expr(initializer)?;
+                    self.yield_allowed = false;
                 }
                 self.define(&name);
             },
+            Stmt::VarDestructure(pattern, initializer) => {
+                for name in pattern.bindings() {
+                    self.declare(name, DeclarationKind::Variable)?;
+                }
+                self.resolve_expr(initializer)?;
+                for name in pattern.bindings() {
+                    self.define(name);
+                }
+            },
+            Stmt::AssignDestructure(pattern, value, distances) => {
+                self.resolve_expr(value)?;
+                *distances = Vec::new();
+                for name in pattern.bindings() {
+                    let distance = self.resolve_local(name);
+                    self.check_assignable(name, distance)?;
+                    distances.push(distance);
+                }
+            },
+            Stmt::ConstDecl(name, initializer) => {
+                self.declare(&name, DeclarationKind::Constant)?;
+                self.resolve_expr(initializer)?;
+                self.define_constant(&name);
+            },
             Stmt::Block(stmts) => {
                 self.begin_scope();
-                self.resolve_ast(stmts)?;
+                self.resolve_stmts(stmts);
                 self.end_scope();
             },
             Stmt::If(condition, then, else_stmt) => {
@@ -135,8 +422,16 @@ impl Resolver {
                 self.resolve_expr(condition)?;
                 self.resolve_stmt(body)?;
             },
+            Stmt::ForIn(_, name, iterable, body) => {
+                self.resolve_expr(iterable)?;
+                self.begin_scope();
+                self.declare(name, DeclarationKind::Variable)?;
+                self.define(name);
+                self.resolve_stmt(body)?;
+                self.end_scope();
+            },
             Stmt::Function(name, parameters, body) => {
-                self.declare(&name)?;
+                self.declare(&name, DeclarationKind::Function)?;
                 self.define(&name);
                 self.resolve_function(name, parameters, body, FuncType::Function)?;
             },
@@ -151,11 +446,11 @@ impl Resolver {
                     self.resolve_expr(expr)?;
                 }
             },
-            Stmt::ClassDecl(name, methods, superclass) => {
+            Stmt::ClassDecl(name, methods, superclass, traits) => {
                 let enclosing_class = self.current_class;
                 self.current_class = ClassType::Class;
 
-                self.declare(&name)?;
+                self.declare(&name, DeclarationKind::Class)?;
                 self.define(&name);
 
                 if let Some(superclass) = superclass {
@@ -166,32 +461,76 @@ impl Resolver {
                     }
                     self.current_class = ClassType::Subclass;
                     self.resolve_expr(superclass)?;
+                }
 
-                    self.begin_scope();
-                    self.scopes.last_mut().unwrap().insert("super".to_string(), true);
+                for trait_expr in traits {
+                    self.resolve_expr(trait_expr)?;
                 }
 
+                // The class scope holds `super` and identifies the class
+                // to its private members
                 self.begin_scope();
-                self.scopes.last_mut().unwrap().insert("this".to_string(), true);
+                if superclass.is_some() {
+                    self.define_keyword("super");
+                }
+                self.resolve_methods(methods)?;
+                self.end_scope();
+
+                self.current_class = enclosing_class;
+            },
+            Stmt::EnumDecl(name, _) => {
+                self.declare(&name, DeclarationKind::Enum)?;
+                self.define(&name);
+            },
+            Stmt::TraitDecl(name, methods) => {
+                let enclosing_class = self.current_class;
+                self.current_class = ClassType::Trait;
+
+                self.declare(&name, DeclarationKind::Trait)?;
+                self.define(&name);
+                self.resolve_methods(methods)?;
 
-                for method in methods {
-                    if let Stmt::Function(name, parameters, body) = method {
-                        let mut func_type = FuncType::Method;
-                        if name.lexeme == "init" {
-                            func_type = FuncType::Initializer;
+                self.current_class = enclosing_class;
+            },
+            Stmt::Match(_, subject, arms) => {
+                self.resolve_expr(subject)?;
+                for arm in arms {
+                    for pattern in arm.patterns.iter_mut() {
+                        if let Pattern::Class(_, expr, _) | Pattern::Variant(_, _, expr, _) = pattern {
+                            self.resolve_expr(expr)?;
                         }
-                        self.resolve_function(name, parameters, body, func_type)?;
                     }
-                    
-                }
 
-                self.end_scope();
+                    // Every alternative must bind the same names so the arm body can use them
+                    let bound = |pattern: &Pattern| {
+                        let mut names: Vec<String> = pattern.bindings()
+                            .iter()
+                            .map(|name| name.lexeme.clone())
+                            .collect();
+                        names.sort();
+                        names
+                    };
+                    let first = &arm.patterns[0];
+                    for pattern in arm.patterns.iter().skip(1) {
+                        if bound(pattern) != bound(first) {
+                            return Err(ResolvingError::InvalidPattern(
+                                pattern.token().clone(),
+                                "Alternative patterns must bind the same names".to_string()
+                            ))
+                        }
+                    }
 
-                if let Some(superclass) = superclass {
+                    self.begin_scope();
+                    for name in first.bindings() {
+                        self.declare(name, DeclarationKind::Variable)?;
+                        self.define(name);
+                    }
+                    if let Some(guard) = &mut arm.guard {
+                        self.resolve_expr(guard)?;
+                    }
+                    self.resolve_stmt(&mut arm.body)?;
                     self.end_scope();
                 }
-
-                self.current_class = enclosing_class;
             }
         };
         Ok(())
@@ -211,32 +550,39 @@ impl Resolver {
                 self.resolve_expr(right)?;
             },
             Expr::Var(name, distance) => {
-                if !self.scopes.is_empty() && self.scopes.last().unwrap().get(&name.lexeme) == Some(&false) {
+                if !self.scopes.is_empty() && self.scopes.last().unwrap().get(&name.lexeme).map(|var| var.defined) == Some(false) {
                     return Err(ResolvingError::ReferencedInInitializer(
                         name.clone(),
                         "Can't read local variable in its own initializer.".to_string())
                     );
                 }
+                self.mark_used(name);
                 *distance = self.resolve_local(name);
             },
             Expr::Assign(name, value, distance) => {
                 self.resolve_expr(value)?;
                 *distance = self.resolve_local(name);
+                self.check_assignable(name, *distance)?;
             },
             Expr::Logical(left, _, right) => {
                 self.resolve_expr(left)?;
                 self.resolve_expr(right)?;
             },
-            Expr::Call(callee, _, arguments) => {
+            Expr::Call(callee, _, arguments, named) => {
                 self.resolve_expr(callee)?;
                 for argument in arguments {
                     self.resolve_expr(argument)?;
                 }
+                for (_, argument) in named {
+                    self.resolve_expr(argument)?;
+                }
             },
             Expr::Get(object, name) => {
+                self.check_private(object, name)?;
                 self.resolve_expr(object)?;
             },
             Expr::Set(object, name, value) => {
+                self.check_private(object, name)?;
                 self.resolve_expr(value)?;
                 self.resolve_expr(object)?;
             },
@@ -253,6 +599,10 @@ impl Resolver {
                     return Err(ResolvingError::ThisOutOfClass(
                         name.clone(),
                         "Can't use 'super' outside of a class".to_string()))
+                } else if self.current_class == ClassType::Trait {
+                    return Err(ResolvingError::ThisOutOfClass(
+                        name.clone(),
+                        "Can't use 'super' in a trait".to_string()))
                 } else if self.current_class != ClassType::Subclass {
                     return Err(ResolvingError::ThisOutOfClass(
                         name.clone(),
@@ -260,57 +610,234 @@ impl Resolver {
                 }
                 *distance = self.resolve_local(name);
             },
+            Expr::Yield(keyword, value) => {
+                if !std::mem::take(&mut self.yield_allowed) {
+                    return Err(ResolvingError::InvalidYield(
+                        keyword.clone(),
+                        "Yield must be a statement, a variable initializer or the value of an assignment".to_string()))
+                }
+                match self.current_function {
+                    FuncType::None => return Err(ResolvingError::InvalidYield(
+                        keyword.clone(),
+                        "Can't yield from top-level code.".to_string())),
+                    FuncType::Initializer => return Err(ResolvingError::InvalidYield(
+                        keyword.clone(),
+                        "Can't yield from an initializer".to_string())),
+                    _ => ()
+                }
+                if let Some(value) = value {
+                    self.resolve_expr(value)?;
+                }
+            },
         };
         Ok(())
     }
 
+    /// Rejects assignments to a local constant.
+    fn check_assignable(&self, name: &Token, distance: Option<usize>) -> ResolveResult<()> {
+        if let Some(distance) = distance {
+            let scope = &self.scopes[self.scopes.len() - 1 - distance];
+            if scope[&name.lexeme].constant {
+                return Err(ResolvingError::AssignToConstant(
+                    name.clone(),
+                    "Can't assign to a constant".to_string()
+                ));
+            }
+        }
+        Ok(())
+    }
+
     fn begin_scope(&mut self) {
         self.scopes.push(HashMap::new());
     }
 
     fn end_scope(&mut self) {
-        self.scopes.pop();
+        let scope = match self.scopes.pop() {
+            Some(scope) => scope,
+            None => return
+        };
+
+        let mut unused: Vec<&Variable> = scope
+            .values()
+            .filter(|variable| !variable.used)
+            .collect();
+        unused.sort_by_key(|variable| variable.token.as_ref().map(|token| token.line));
+        for variable in unused {
+            let message = match variable.kind {
+                VarKind::Variable => "Local variable is never read",
+                VarKind::Parameter => "Parameter is never used",
+                _ => continue
+            };
+            if let Some(token) = &variable.token {
+                if !token.lexeme.starts_with('_') {
+                    self.warn(token, message);
+                }
+            }
+        }
     }
 
-    fn declare(&mut self, name: &Token) -> ResolveResult<()> {
-        if let Some(scope) = self.scopes.last_mut() {
-            if let Some(_) = scope.insert(name.lexeme.to_string(), false) {
-                return Err(ResolvingError::AlreadyExists(
-                    name.clone(),
-                    "Already a variable with this name in the scope".to_string()
-                ))
+    fn warn(&mut self, token: &Token, message: &str) {
+        self.warnings.push(ResolvingWarning {
+            token: token.clone(),
+            message: message.to_string(),
+        });
+    }
+
+    fn declare(&mut self, name: &Token, kind: DeclarationKind) -> ResolveResult<()> {
+        if self.scopes.is_empty() {
+            self.global_declarations.insert(name.lexeme.clone());
+            self.declarations.push(Declaration { token: name.clone(), kind, global: true });
+            return Ok(());
+        }
+
+        // Names starting with an underscore are meant to be ignored
+        if !name.lexeme.starts_with('_') {
+            let enclosing = self.scopes[..self.scopes.len() - 1]
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(&name.lexeme));
+            let shadowed = match enclosing {
+                Some(variable) => variable.token.as_ref().map(|token| token.line),
+                None => self.declarations
+                    .iter()
+                    .find(|declaration| declaration.global && declaration.token.lexeme == name.lexeme)
+                    .map(|declaration| declaration.token.line),
+            };
+            if let Some(line) = shadowed {
+                self.warn(name, &format!("Shadows a variable declared on line {}", line));
             }
         }
+
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.contains_key(&name.lexeme) {
+            return Err(ResolvingError::AlreadyExists(
+                name.clone(),
+                "Already a variable with this name in the scope".to_string()
+            ))
+        }
+        scope.insert(name.lexeme.to_string(), Variable {
+            defined: false,
+            constant: false,
+            used: false,
+            kind: match kind {
+                DeclarationKind::Variable | DeclarationKind::Constant => VarKind::Variable,
+                DeclarationKind::Parameter => VarKind::Parameter,
+                _ => VarKind::Declaration,
+            },
+            token: Some(name.clone()),
+            declaration: Some(self.declarations.len()),
+        });
+        self.declarations.push(Declaration { token: name.clone(), kind, global: false });
         Ok(())
     }
 
     fn define(&mut self, name: &Token) {
+        if let Some(variable) = self.scopes.last_mut().and_then(|scope| scope.get_mut(&name.lexeme)) {
+            variable.defined = true;
+        }
+    }
+
+    fn define_constant(&mut self, name: &Token) {
+        if let Some(variable) = self.scopes.last_mut().and_then(|scope| scope.get_mut(&name.lexeme)) {
+            variable.defined = true;
+            variable.constant = true;
+        }
+    }
+
+    fn define_keyword(&mut self, keyword: &str) {
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.to_string(), true);
-        }        
+            scope.insert(keyword.to_string(), Variable {
+                defined: true,
+                constant: true,
+                used: false,
+                kind: VarKind::Keyword,
+                token: None,
+                declaration: None,
+            });
+        }
+    }
+
+    /// Records that a local variable is read.
+    fn mark_used(&mut self, name: &Token) {
+        if let Some(variable) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&name.lexeme)) {
+            variable.used = true;
+        }
     }
 
     #[must_use]
     fn resolve_local(&mut self, name: &Token) -> Option<usize> {
         for (i,scope) in self.scopes.iter().rev().enumerate() {
-            if scope.contains_key(&name.lexeme) {
+            if let Some(variable) = scope.get(&name.lexeme) {
+                if let Some(declaration) = variable.declaration {
+                    self.references.push(Reference { token: name.clone(), declaration: Some(declaration) });
+                }
                 return Some(i)
             }
         };
 
+        // A global declared already is there however soon the reference runs
+        let deferred = self.current_function != FuncType::None
+            || self.in_test
+            || self.global_declarations.contains(&name.lexeme);
+        self.global_references.push((name.clone(), deferred));
+        if name.token_type == TokenType::Identifier {
+            self.references.push(Reference { token: name.clone(), declaration: None });
+        }
         None
     }
 
-    fn resolve_function(&mut self, name: &Token, parameters: &[Token], body: &mut [Stmt], func_type: FuncType) -> ResolveResult<()> {
+    /// Private members can only be reached through `this` in a class's methods.
+    fn check_private(&self, object: &Expr, name: &Token) -> ResolveResult<()> {
+        if name.token_type != TokenType::PrivateIdentifier {
+            return Ok(());
+        }
+        let message = match self.current_class {
+            ClassType::None => "Can't access a private member outside of a class",
+            ClassType::Trait => "Can't use private members in a trait",
+            _ if !matches!(object, Expr::This(_, _)) => "Private members can only be accessed through 'this'",
+            _ => return Ok(())
+        };
+        Err(ResolvingError::PrivateAccess(name.clone(), message.to_string()))
+    }
+
+    /// Resolves the methods of a class or trait in a scope holding `this`.
+    fn resolve_methods(&mut self, methods: &mut [Stmt]) -> ResolveResult<()> {
+        self.begin_scope();
+        self.define_keyword("this");
+
+        for method in methods {
+            if let Stmt::Function(name, parameters, body) = method {
+                if name.token_type == TokenType::PrivateIdentifier && self.current_class == ClassType::Trait {
+                    return Err(ResolvingError::PrivateAccess(
+                        name.clone(),
+                        "Can't use private members in a trait".to_string()))
+                }
+                let mut func_type = FuncType::Method;
+                if name.lexeme == "init" {
+                    func_type = FuncType::Initializer;
+                }
+                self.resolve_function(name, parameters, body, func_type)?;
+            }
+        }
+
+        self.end_scope();
+        Ok(())
+    }
+
+    fn resolve_function(&mut self, name: &Token, parameters: &mut [Param], body: &mut [Stmt], func_type: FuncType) -> ResolveResult<()> {
         let enclosing_function = self.current_function;
         self.current_function = func_type;
         
         self.begin_scope();
         for param in parameters {
-            self.declare(&param)?;
-            self.define(&param);
+            // Defaults are evaluated in the call's environment and can see earlier parameters
+            if let Some(default) = &mut param.default {
+                self.resolve_expr(default)?;
+            }
+            self.declare(&param.name, DeclarationKind::Parameter)?;
+            self.define(&param.name);
         }
-        self.resolve_ast(body)?;
+        self.resolve_stmts(body);
         self.end_scope();
 
         self.current_function = enclosing_function;
@@ -320,4 +847,12 @@ impl Resolver {
 
 
     
-}
\ No newline at end of file
+}
+
+/// The first token of a statement, looking into blocks.
+fn first_token(stmt: &Stmt) -> Option<&Token> {
+    match stmt {
+        Stmt::Block(stmts) => stmts.iter().find_map(first_token),
+        _ => stmt.token(),
+    }
+}
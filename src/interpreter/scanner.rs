@@ -1,8 +1,13 @@
 use super::token::{Token,TokenType};
 use super::Literal;
+use super::lox_types::{LoxBigInt, LoxDecimal};
 use super::{Error, ErrorReport};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// A line comment starting with this directive as a word of its own silences warnings on its line.
+const IGNORE_DIRECTIVE: &str = "lox:ignore";
 
 pub struct Scanner {
     source: String,
@@ -13,6 +18,18 @@ pub struct Scanner {
     current: usize,
     line: usize,
     keywords: HashMap<String, TokenType>,
+    ignored_lines: HashSet<usize>,
+    comments: Vec<Comment>,
+}
+
+/// A comment, kept so the formatter can print it back.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    // The comment's source, including its delimiters
+    pub text: String,
+    pub line: usize,
+    // The number of tokens scanned before the comment
+    pub token_index: usize,
 }
 
 impl ErrorReport for Scanner {
@@ -65,29 +82,53 @@ impl Scanner {
             line: 1,
             keywords: vec![
                 ("and", TokenType::And),
+                ("assert", TokenType::Assert),
+                ("case", TokenType::Case),
                 ("class", TokenType::Class),
+                ("const", TokenType::Const),
                 ("else", TokenType::Else),
+                ("enum", TokenType::Enum),
                 ("false", TokenType::False),
                 ("for", TokenType::For),
                 ("fun", TokenType::Fun),
                 ("if", TokenType::If),
+                ("in", TokenType::In),
+                ("is", TokenType::Is),
+                ("match", TokenType::Match),
                 ("nil", TokenType::Nil),
                 ("or", TokenType::Or),
                 ("print", TokenType::Print),
                 ("return", TokenType::Return),
                 ("super", TokenType::Super),
                 ("this", TokenType::This),
+                ("trait", TokenType::Trait),
                 ("true", TokenType::True),
                 ("var", TokenType::Var),
                 ("while", TokenType::While),
+                ("with", TokenType::With),
+                ("yield", TokenType::Yield),
             ]
             .into_iter()
             .map(|(k, v)| (String::from(k), v))
             .collect(),
+            ignored_lines: HashSet::new(),
+            comments: Vec::new(),
         }
     }
 
+    /// Lines ending with a `// lox:ignore` comment, whose warnings are silenced.
+    pub fn ignored_lines(&self) -> &HashSet<usize> {
+        &self.ignored_lines
+    }
+
+    /// The comments skipped while scanning, including a `#!` line, in source order.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
     pub fn scan_tokens(&mut self) -> Result<Vec<Token>,Error> {
+        self.shebang();
+
         while !self.is_at_end() {
             self.start = self.current;
             self.scan_token();
@@ -108,14 +149,23 @@ impl Scanner {
             '{' => self.add_char_token(TokenType::LeftBrace),
             '}' => self.add_char_token(TokenType::RightBrace),
             ',' => self.add_char_token(TokenType::Comma),
+            ':' => self.add_char_token(TokenType::Colon),
+            '.' if self.peek() == '.' && self.peek_n(1) == '.' => {
+                self.advance();
+                self.advance();
+                self.add_char_token(TokenType::Ellipsis)
+            },
             '.' => self.add_char_token(TokenType::Dot),
             '-' => self.add_char_token(TokenType::Minus),
             '+' => self.add_char_token(TokenType::Plus),
             ';' => self.add_char_token(TokenType::Semicolon),
             '*' => self.add_char_token(TokenType::Star),
+            '%' => self.add_char_token(TokenType::Percent),
+            '~' if self.matches('/') => self.add_char_token(TokenType::TildeSlash),
             '!' if self.matches('=') => self.add_char_token(TokenType::BangEqual),
             '!' => self.add_char_token(TokenType::Bang),
             '=' if self.matches('=') => self.add_char_token(TokenType::EqualEqual),
+            '=' if self.matches('>') => self.add_char_token(TokenType::FatArrow),
             '=' => self.add_char_token(TokenType::Equal),
             '<' if self.matches('=') => self.add_char_token(TokenType::LessEqual),
             '<' => self.add_char_token(TokenType::Less),
@@ -125,18 +175,64 @@ impl Scanner {
                 while self.peek() != '\n' && !self.is_at_end() {
                     self.advance();
                 }
+                let comment = &self.source[self.start + 2..self.current];
+                let directive = comment.trim_start().strip_prefix(IGNORE_DIRECTIVE);
+                if directive.is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+                    self.ignored_lines.insert(self.line);
+                }
+                self.add_comment(self.line);
             },
+            '/' if self.matches('*') => self.block_comment(),
             '/' => self.add_char_token(TokenType::Slash),
             ' ' | '\r' | '\t' => (),
             '\n' => {self.line += 1},
             '"' => self.string(), 
             '0'..='9' => self.number(), 
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),           
+            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+            '#' if self.is_alpha(self.peek()) => self.private_identifier(),           
             _ => self.error(self.line, &format!("Unexpected character: {}",c)),
             // _ => (),
         };
     }  
 
+    /// Skips a `#!` interpreter line so scripts can be executed directly.
+    fn shebang(&mut self) {
+        if self.current == 0 && self.peek() == '#' && self.peek_n(1) == '!' {
+            while self.peek() != '\n' && !self.is_at_end() {
+                self.advance();
+            }
+            self.add_comment(1);
+        }
+    }
+
+    /// Skips a block comment, which may contain nested block comments.
+    fn block_comment(&mut self) {
+        let start_line = self.line;
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                self.error(start_line, "Unterminated block comment.");
+                return;
+            }
+            match self.advance() {
+                '/' if self.matches('*') => depth += 1,
+                '*' if self.matches('/') => depth -= 1,
+                '\n' => self.line += 1,
+                _ => ()
+            }
+        }
+        self.add_comment(start_line);
+    }
+
+    fn add_comment(&mut self, line: usize) {
+        self.comments.push(Comment {
+            text: self.source[self.start..self.current].to_string(),
+            line,
+            token_index: self.tokens.len(),
+        });
+    }
+
     fn matches(&mut self, expected: char) -> bool {
         if self.is_at_end() || (self.char_ptr.offset(0) != expected) {
             false
@@ -182,20 +278,145 @@ impl Scanner {
     }
 
     fn number(&mut self) {
-        while self.is_digit(self.peek()) {
-            self.advance();
+        let first = self.source.as_bytes()[self.start] as char;
+        if first == '0' {
+            match self.peek() {
+                'x' | 'X' => return self.radix_number(16, "hexadecimal"),
+                'o' | 'O' => return self.radix_number(8, "octal"),
+                'b' | 'B' => return self.radix_number(2, "binary"),
+                _ => ()
+            }
         }
 
+        if !self.digits(10, true) {
+            return;
+        }
+
+        let mut has_fraction = false;
         if self.peek() == '.' && self.is_digit(self.peek_n(1)) {
+            has_fraction = true;
+            self.advance();
+
+            if !self.digits(10, false) {
+                return;
+            }
+        }
+
+        let mut has_exponent = false;
+        if matches!(self.peek(), 'e' | 'E') {
+            has_exponent = true;
+            self.advance();
+            if matches!(self.peek(), '+' | '-') {
+                self.advance();
+            }
+            if !self.is_digit(self.peek()) {
+                self.error(self.line, "Expected digits in the exponent of a number literal");
+                return;
+            }
+            if !self.digits(10, false) {
+                return;
+            }
+        }
+
+        let text = self.source[self.start..self.current].replace('_', "");
+        let suffix = self.peek();
+        if matches!(suffix, 'n' | 'd') && !self.is_alnum(self.peek_n(1)) {
             self.advance();
+            if has_exponent {
+                self.error(self.line, "Big integer and decimal literals can't have an exponent");
+            } else if suffix == 'd' {
+                let literal = LoxDecimal::parse(&text).unwrap();
+                self.add_token(TokenType::Number, Some(Literal::Decimal(Rc::new(literal))));
+            } else if has_fraction {
+                self.error(self.line, "Big integer literal can't have a fraction");
+            } else {
+                let literal = LoxBigInt::parse(&text).unwrap();
+                self.add_token(TokenType::Number, Some(Literal::BigInt(Rc::new(literal))));
+            }
+            return;
+        }
+
+        if has_fraction || has_exponent {
+            let literal = text.parse::<f64>().unwrap();
+            if literal.is_infinite() {
+                self.error(self.line, "Number literal too large");
+                return;
+            }
+            self.add_token(TokenType::Number, Some(Literal::Num(literal)));
+            return;
+        }
+
+        // Literals without a fraction or exponent are integers
+        match text.parse::<i64>() {
+            Ok(literal) => self.add_token(TokenType::Number, Some(Literal::Int(literal))),
+            Err(_) => self.error(self.line, "Integer literal too large, use the 'n' suffix for a big integer"),
+        }
+    }
+
+    /// Scans a literal with a `0x`, `0o` or `0b` prefix.  The leading zero
+    /// has already been consumed.
+    fn radix_number(&mut self, radix: u32, name: &str) {
+        let prefix = self.advance();
+        if !self.is_radix_digit(self.peek(), radix) {
+            self.error(self.line, &format!("Expected {} digits after '0{}'", name, prefix));
+            return;
+        }
+        if !self.digits(radix, false) {
+            return;
+        }
 
-            while self.is_digit(self.peek()) {
+        let digits = self.source[self.start + 2..self.current].replace('_', "");
+        let big = self.peek() == 'n' && !self.is_alnum(self.peek_n(1));
+        if big {
+            self.advance();
+        } else if self.is_alnum(self.peek()) {
+            let c = self.peek();
+            self.error(self.line, &format!("Invalid digit '{}' in {} literal", c, name));
+            while self.is_alnum(self.peek()) {
                 self.advance();
             }
+            return;
+        }
+
+        if big {
+            let base = LoxBigInt::from_i64(radix as i64);
+            let literal = digits.chars().fold(LoxBigInt::zero(), |value, c| {
+                let digit = LoxBigInt::from_i64(c.to_digit(radix).unwrap() as i64);
+                &(&value * &base) + &digit
+            });
+            self.add_token(TokenType::Number, Some(Literal::BigInt(Rc::new(literal))));
+            return;
         }
 
-        let literal = (&self.source[self.start..self.current]).parse::<f64>().unwrap();
-        self.add_token(TokenType::Number, Some(Literal::Num(literal)));
+        match i64::from_str_radix(&digits, radix) {
+            Ok(literal) => self.add_token(TokenType::Number, Some(Literal::Int(literal))),
+            Err(_) => self.error(self.line, "Integer literal too large, use the 'n' suffix for a big integer"),
+        }
+    }
+
+    /// Consumes a run of digits in the given radix, which may be split by `_`
+    /// separators.  A separator has to sit between two digits; a misplaced one
+    /// is reported and false is returned.
+    fn digits(&mut self, radix: u32, after_digit: bool) -> bool {
+        let mut after_digit = after_digit;
+        loop {
+            let c = self.peek();
+            if self.is_radix_digit(c, radix) {
+                after_digit = true;
+            } else if c == '_' {
+                if !after_digit || !self.is_radix_digit(self.peek_n(1), radix) {
+                    self.error(self.line, "Digit separator '_' must be between two digits");
+                    while self.is_alnum(self.peek()) {
+                        self.advance();
+                    }
+                    return false;
+                }
+                after_digit = false;
+            } else {
+                return true;
+            }
+            self.advance();
+        }
     }
 
     fn identifier(&mut self) {
@@ -218,10 +439,22 @@ impl Scanner {
         self.add_token(token_type, literal);
     }
 
+    /// Scans a `#name` used for private fields and methods.
+    fn private_identifier(&mut self) {
+        while self.is_alnum(self.peek()) {
+            self.advance();
+        }
+        self.add_token(TokenType::PrivateIdentifier, None);
+    }
+
     fn is_digit(&self, c: char) -> bool {
         matches!(c, '0'..='9')
     }
 
+    fn is_radix_digit(&self, c: char, radix: u32) -> bool {
+        c.is_digit(radix)
+    }
+
     fn is_alpha(&self, c: char) -> bool {
         matches!(c, 'a'..='z' | 'A'..='Z' | '_')
     }
@@ -235,12 +468,15 @@ impl Scanner {
     }
 
     fn add_token(&mut self, token_type: TokenType, literal: Option<Literal>) {
-        self.tokens.push(Token::new(
+        let mut token = Token::new(
             token_type,
             &self.source[self.start..self.current],
             literal,
-            self.line)
+            self.line
         );
+        let line_start = self.source[..self.start].rfind('\n').map_or(0, |newline| newline + 1);
+        token.column = self.start - line_start;
+        self.tokens.push(token);
     }
 
     fn advance(&mut self) -> char {
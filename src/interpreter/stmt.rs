@@ -1,32 +1,94 @@
-use super::{Token,Expr};
+use super::{Token,Expr,MatchArm,Destructure};
+
+/// A function parameter.  Parameters may have a default value evaluated at call time,
+/// and the last one may be a rest parameter collecting the remaining arguments in a list.
+#[derive(Debug,Clone)]
+pub struct Param {
+    pub name: Token,
+    pub default: Option<Expr>,
+    pub rest: bool,
+}
 
 #[derive(Debug,Clone)]
 pub enum Stmt {
     ExprStmt(Expr),
-    PrintStmt(Expr),
+    PrintStmt(Token, Expr),
     VarDecl(Token,Option<Expr>),
+    VarDestructure(Destructure,Expr),
+    AssignDestructure(Destructure,Expr,Vec<Option<usize>>),
+    ConstDecl(Token,Expr),
     Block(Vec<Stmt>),
     If(Expr,Box<Stmt>,Option<Box<Stmt>>),
     While(Expr, Box<Stmt>),
-    Function(Token, Vec<Token>, Vec<Stmt>),
+    ForIn(Token, Token, Expr, Box<Stmt>),
+    Function(Token, Vec<Param>, Vec<Stmt>),
     Return(Token, Option<Expr>),
-    ClassDecl(Token, Vec<Stmt>, Option<Expr>)
+    ClassDecl(Token, Vec<Stmt>, Option<Expr>, Vec<Expr>),
+    TraitDecl(Token, Vec<Stmt>),
+    EnumDecl(Token, Vec<(Token, Vec<Token>)>),
+    Match(Token, Expr, Vec<MatchArm>),
+    // The keyword, condition, optional message and the condition's source text
+    Assert(Token, Box<Expr>, Option<Box<Expr>>, String),
+    // A test block named by a string, skipped unless running tests
+    Test(Token, String, Vec<Stmt>),
+}
+
+impl Stmt {
+    /// The token the statement starts with, or None for blocks, whose statements
+    /// have their own.  `if` and `while` statements with a literal condition
+    /// take the token of their body.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            Stmt::Block(_) => None,
+            Stmt::ExprStmt(expr) => expr.token(),
+            Stmt::VarDestructure(pattern, _) |
+            Stmt::AssignDestructure(pattern, _, _) => Some(pattern.token()),
+            Stmt::If(condition, then, _) => condition.token().or_else(|| then.token()),
+            Stmt::While(condition, body) => condition.token().or_else(|| body.token()),
+            Stmt::PrintStmt(token, _) |
+            Stmt::VarDecl(token, _) |
+            Stmt::ConstDecl(token, _) |
+            Stmt::ForIn(token, _, _, _) |
+            Stmt::Function(token, _, _) |
+            Stmt::Return(token, _) |
+            Stmt::ClassDecl(token, _, _, _) |
+            Stmt::TraitDecl(token, _) |
+            Stmt::EnumDecl(token, _) |
+            Stmt::Match(token, _, _) |
+            Stmt::Assert(token, _, _, _) |
+            Stmt::Test(token, _, _) => Some(token),
+        }
+    }
+
+    /// The line the statement starts on, or None for blocks.
+    pub fn line(&self) -> Option<usize> {
+        self.token().map(|token| token.line)
+    }
 }
 
 impl std::fmt::Display for Stmt {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
             Stmt::ExprStmt(ref expr) => write!(f, "({})", expr),
-            Stmt::PrintStmt(ref expr) => write!(f, "(print {})", expr),
+            Stmt::PrintStmt(_, ref expr) => write!(f, "(print {})", expr),
             Stmt::VarDecl(ref var, _) => write!(f, "var {}", var),
+            Stmt::VarDestructure(ref pattern, ref expr) => write!(f, "var {} = {}", pattern, expr),
+            Stmt::AssignDestructure(ref pattern, ref expr, _) => write!(f, "{} = {}", pattern, expr),
+            Stmt::ConstDecl(ref name, _) => write!(f, "const {}", name),
             Stmt::Block(ref stmts) => write!(f, "<block of statements with len {}>", stmts.len()),
             Stmt::If(ref expr, ref stmt, ref else_stmt) => {
                 write!(f, "if ({}) then ({}) else {:?}", expr, stmt, else_stmt)
             },
             Stmt::While(ref expr, ref stmt) => write!(f, "while ({}): {}", expr, stmt),
+            Stmt::ForIn(_, ref name, ref expr, ref stmt) => write!(f, "for ({} in {}): {}", name.lexeme, expr, stmt),
             Stmt::Function(ref name, _,_) => write!(f, "<function {}>", name),
             Stmt::Return(_, ref expr) => write!(f, "return {:?}", expr),
-            Stmt::ClassDecl(ref name,_, _) => write!(f, "<class {}>", name),
+            Stmt::ClassDecl(ref name, _, _, _) => write!(f, "<class {}>", name),
+            Stmt::TraitDecl(ref name, _) => write!(f, "<trait {}>", name),
+            Stmt::EnumDecl(ref name, _) => write!(f, "<enum {}>", name),
+            Stmt::Match(_, ref expr, ref arms) => write!(f, "match ({}) <{} arms>", expr, arms.len()),
+            Stmt::Assert(_, ref expr, _, _) => write!(f, "(assert {})", expr),
+            Stmt::Test(_, ref name, ref stmts) => write!(f, "<test \"{}\" with len {}>", name, stmts.len()),
         }
     }
 }
\ No newline at end of file
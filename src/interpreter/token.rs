@@ -15,12 +15,14 @@ pub enum TokenType {
     LeftBracket,
     RightBracket,
     Comma,
+    Colon,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
 
     // One or two character tokens.
     Bang,
@@ -31,29 +33,43 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    TildeSlash,
+    FatArrow,
+    Ellipsis,
 
     // Literals.
     Identifier,
+    PrivateIdentifier,
     String,
     Number,
 
     // Keywords.
     And,
+    Assert,
+    Case,
     Class,
+    Const,
     Else,
+    Enum,
     False,
     Fun,
     For,
     If,
+    In,
+    Is,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Trait,
     True,
     Var,
     While,
+    With,
+    Yield,
 
     Eof,
 }
@@ -64,6 +80,8 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<Literal>,
     pub line: usize,
+    // Offset of the token's first character in its line, set by the scanner
+    pub column: usize,
 }
 
 impl Display for Token {
@@ -78,7 +96,8 @@ impl Token {
             token_type,
             lexeme: String::from(lexeme),
             literal,
-            line
+            line,
+            column: 0,
         }
     }
 }
\ No newline at end of file
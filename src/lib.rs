@@ -8,12 +8,29 @@ use interpreter::*;
 
 use std::{
     fs::read_to_string,
-    io,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
 };
 
+/// What to print instead of running a script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dump {
+    Tokens,
+    Ast,
+    AstJson,
+    // The syntax tree with the scope distances found by the resolver
+    Resolved,
+    ResolvedJson,
+}
+
 pub struct Lox {
     had_error: bool,
     had_runtime_error: bool,
+    // Reports references to undefined globals before running
+    strict: bool,
+    // Folds constants and removes dead branches before running
+    optimize: bool,
+    dump: Option<Dump>,
     interpreter: Interpreter,
 }    
 
@@ -30,21 +47,51 @@ impl Lox {
         Lox{
             had_error: false,
             had_runtime_error: false,
+            strict: false,
+            optimize: false,
+            dump: None,
             interpreter: Interpreter::new(),
         }
     }
 
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize;
+    }
+
+    /// Prints the tokens or syntax tree of scripts instead of running them.
+    pub fn set_dump(&mut self, dump: Option<Dump>) {
+        self.dump = dump;
+    }
+
     pub fn run_file(&mut self, path: &str) -> std::io::Result<()> {
         let contents = read_to_string(path)?;
         self.run(&contents);
+        self.exit_on_error();
+        Ok(())
+    }
+
+    /// Runs a script under the debugger, which starts paused at the first
+    /// statement and reads its commands from `commands`.
+    pub fn debug_file<R: BufRead + 'static>(&mut self, path: &str, commands: R) -> std::io::Result<()> {
+        let contents = read_to_string(path)?;
+        self.interpreter.set_debugger(Some(Debugger::new(&contents, Box::new(commands))));
+        self.run(&contents);
+        self.interpreter.set_debugger(None);
+        self.exit_on_error();
+        Ok(())
+    }
 
+    fn exit_on_error(&self) {
         if self.had_error {
             std::process::exit(65);
         }
         if self.had_runtime_error {
             std::process::exit(70);
         }
-        Ok(())
     }
     
     pub fn run_prompt(&mut self) -> std::io::Result<()> { 
@@ -62,17 +109,26 @@ impl Lox {
     }
     
     fn run(&mut self, source: &str) {
+        if let Some(stmts) = self.compile(source) {
+            self.interpreter.interpret(stmts)
+        }
+    }
+
+    /// Scans, parses and resolves a program, reporting any errors and warnings.
+    /// Returns None if the program can't be run.
+    fn compile(&mut self, source: &str) -> Option<Vec<Stmt>> {
         // Scanning
         let mut scanner = Scanner::new(source);
         let result = scanner.scan_tokens();
         if let Ok(ref tokens) = result {
-            // tokens.iter().for_each(|token| {
-                // println!("{}", token);
-            // });
+            if self.dump == Some(Dump::Tokens) {
+                print!("{}", dump_tokens(tokens));
+                return None;
+            }
         } 
         else if let Err(ref e) = result {
             self.had_error = true;
-            return;
+            return None;
         }
         
         // Parsing 
@@ -81,19 +137,178 @@ impl Lox {
         let stmts = parser.parse();
         match stmts {
             Ok(mut stmts) => {
+                match self.dump {
+                    Some(Dump::Ast) => {
+                        print!("{}", dump_tree(&stmts, false));
+                        return None;
+                    },
+                    Some(Dump::AstJson) => {
+                        print!("{}", dump_json(&stmts, false));
+                        return None;
+                    },
+                    _ => ()
+                }
+
                 let mut resolver = Resolver::new();
-                if let Err(e) = resolver.resolve_ast(&mut stmts) {
-                    println!("{}", e);
-                    self.had_error = true;
+                let mut errors = match resolver.resolve_ast(&mut stmts) {
+                    Ok(()) => Vec::new(),
+                    Err(errors) => errors,
                 };
-                if !self.had_error {
-                    self.interpreter.interpret(stmts)
+                if self.strict {
+                    errors.extend(resolver.check_globals(&self.interpreter.global_names()));
+                }
+                if !errors.is_empty() {
+                    errors.sort_by_key(|e| e.token().line);
+                    errors.iter().for_each(|e| println!("{}", e));
+                    self.had_error = true;
+                    return None;
+                }
+
+                // Warnings go to stderr and don't stop the program from running
+                let mut warnings: Vec<_> = resolver.warnings()
+                    .iter()
+                    .filter(|warning| !scanner.ignored_lines().contains(&warning.token.line))
+                    .collect();
+                warnings.sort_by_key(|warning| warning.token.line);
+                warnings.iter().for_each(|warning| eprintln!("{}", warning));
+
+                if self.optimize {
+                    stmts = Optimizer::new().optimize(stmts);
                 }
+                match self.dump {
+                    Some(Dump::Resolved) => print!("{}", dump_tree(&stmts, true)),
+                    Some(Dump::ResolvedJson) => print!("{}", dump_json(&stmts, true)),
+                    _ => return Some(stmts)
+                }
+                None
             },
             Err(e) => {
                 println!("{}", e);
                 self.had_runtime_error = true;
+                None
+            }
+        }
+    }
+
+    /// Formats a script as canonical Lox source, or returns None if it doesn't parse.
+    pub fn format(&mut self, source: &str) -> Option<String> {
+        let mut scanner = Scanner::new(source);
+        let tokens = match scanner.scan_tokens() {
+            Ok(tokens) => tokens,
+            Err(_) => {
+                self.had_error = true;
+                return None;
+            }
+        };
+
+        let mut parser = Parser::new(tokens.clone());
+        match parser.parse() {
+            Ok(stmts) => Some(Formatter::new(&tokens, scanner.comments()).format(&stmts)),
+            Err(e) => {
+                println!("{}", e);
+                self.had_error = true;
+                None
             }
+        }
+    }
+
+    /// Prints a script formatted, or with `check` only reports whether it
+    /// already is.  Returns whether the file was already formatted, or None
+    /// if it doesn't parse.
+    pub fn format_file(&mut self, path: &str, check: bool) -> std::io::Result<Option<bool>> {
+        let contents = read_to_string(path)?;
+        let formatted = match self.format(&contents) {
+            Some(formatted) => formatted,
+            None => return Ok(None),
         };
+
+        if check {
+            if formatted != contents {
+                println!("{} is not formatted", path);
+            }
+        } else {
+            print!("{}", formatted);
+        }
+        Ok(Some(formatted == contents))
+    }
+
+    /// Serves the Language Server Protocol until the client exits, reading messages
+    /// from `input` and writing replies to `output`.  Returns whether the client
+    /// shut the server down before exiting.
+    pub fn run_language_server<R: BufRead, W: Write>(&mut self, input: R, output: W) -> std::io::Result<bool> {
+        LanguageServer::new(self.interpreter.global_names()).run(input, output)
+    }
+
+    /// Runs the test blocks in the given files, or in the `.lox` files under
+    /// the given directories.  Each test runs in a fresh interpreter, after the rest
+    /// of its file, so tests can't see what other tests did.  Returns whether every
+    /// test passed.  When the rest of a file fails, so does each of its tests.
+    pub fn run_tests(&mut self, paths: &[String]) -> std::io::Result<bool> {
+        let mut files = Vec::new();
+        for path in paths {
+            collect_lox_files(Path::new(path), &mut files)?;
+        }
+
+        let (mut passed, mut failed) = (0, 0);
+        for file in files {
+            let contents = read_to_string(&file)?;
+            let stmts = match self.compile(&contents) {
+                Some(stmts) => stmts,
+                None => {
+                    println!("FAIL {}: doesn't compile", file.display());
+                    self.had_error = false;
+                    self.had_runtime_error = false;
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let (tests, prelude): (Vec<Stmt>, Vec<Stmt>) = stmts
+                .into_iter()
+                .partition(|stmt| matches!(stmt, Stmt::Test(_, _, _)));
+            for test in tests {
+                if let Stmt::Test(_, name, body) = test {
+                    let mut interpreter = Interpreter::new();
+                    if let Err(e) = interpreter.run(&prelude) {
+                        println!("FAIL {}: {}", file.display(), name);
+                        println!("    error outside of tests: {}", e);
+                        failed += 1;
+                        continue;
+                    }
+                    match interpreter.run_test(&body) {
+                        Ok(()) => {
+                            println!("PASS {}: {}", file.display(), name);
+                            passed += 1;
+                        },
+                        Err(e) => {
+                            println!("FAIL {}: {}", file.display(), name);
+                            println!("    {}", e);
+                            failed += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        println!("{} passed, {} failed", passed, failed);
+        Ok(failed == 0)
+    }
+}
+
+fn collect_lox_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|extension| extension == "lox") {
+            collect_lox_files(&entry, files)?;
+        }
     }
+    Ok(())
 }
@@ -3,20 +3,82 @@
 #![allow(unused_variables)]
 #![allow(non_camel_case_types)]
 
-use rustylox::Lox;
+use rustylox::{Dump, Lox};
 
 use std::{
     env,
     error::Error,
+    io,
 };
 
-mod interpreter;
-
 fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
     let mut lox = Lox::new();
+    if let Some(position) = args.iter().position(|arg| arg == "--strict") {
+        args.remove(position);
+        lox.set_strict(true);
+    }
+    if let Some(position) = args.iter().position(|arg| arg == "--optimize") {
+        args.remove(position);
+        lox.set_optimize(true);
+    }
+    let dumps = [
+        ("--dump-tokens", Dump::Tokens),
+        ("--dump-ast", Dump::Ast),
+        ("--dump-ast=json", Dump::AstJson),
+        ("--dump-resolved", Dump::Resolved),
+        ("--dump-resolved=json", Dump::ResolvedJson),
+    ];
+    for (flag, dump) in dumps {
+        if let Some(position) = args.iter().position(|arg| arg == flag) {
+            args.remove(position);
+            lox.set_dump(Some(dump));
+        }
+    }
+    if args.get(1).is_some_and(|arg| arg == "fmt") {
+        let check = match args.iter().position(|arg| arg == "--check") {
+            Some(position) => {
+                args.remove(position);
+                true
+            },
+            None => false
+        };
+        if args.len() != 3 {
+            println!("Usage: rustylox fmt [--check] script");
+            return Ok(());
+        }
+        match lox.format_file(&args[2], check)? {
+            None => std::process::exit(65),
+            Some(false) if check => std::process::exit(1),
+            Some(_) => return Ok(()),
+        }
+    }
+    if args.get(1).is_some_and(|arg| arg == "debug") {
+        if args.len() != 3 {
+            println!("Usage: rustylox debug script");
+            return Ok(());
+        }
+        lox.debug_file(&args[2], io::stdin().lock())?;
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|arg| arg == "lsp") {
+        if !lox.run_language_server(io::stdin().lock(), io::stdout().lock())? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|arg| arg == "test") {
+        let paths = match args.len() {
+            2 => vec![String::from(".")],
+            _ => args[2..].to_vec(),
+        };
+        if !lox.run_tests(&paths)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     match args.len() {
-        x if x > 2 => println!("Usage: rustylox [script]"),
+        x if x > 2 => println!("Usage: rustylox [--strict] [--optimize] [--dump-tokens | --dump-ast[=json] | --dump-resolved[=json]] [script]\n       rustylox [--strict] [--optimize] test [paths...]\n       rustylox fmt [--check] script\n       rustylox debug script\n       rustylox lsp"),
         x if x == 2 => lox.run_file(&args[1])?,
         _ => lox.run_prompt()?,
     }
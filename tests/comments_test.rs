@@ -0,0 +1,18 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_block_comment() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/comments/block_comment.lox").unwrap();
+    }
+
+    #[test]
+    fn run_shebang() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/comments/shebang.lox").unwrap();
+    }
+}
@@ -0,0 +1,18 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_const() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/const/const.lox").unwrap();
+    }
+
+    #[test]
+    fn run_assign_global() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/const/assign_global.lox").unwrap();
+    }
+}
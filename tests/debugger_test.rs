@@ -0,0 +1,67 @@
+use rustylox::Lox;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the example under the debugger, returning everything it printed.
+    fn debug(commands: &str) -> String {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rustylox"))
+            .args(["debug", "./example/debugger/debugger.lox"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(commands.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn debug_breakpoints() {
+        let transcript = debug("break 3\ncontinue\nbacktrace\nlocals\nprint n * 10\nfinish\ndelete 3\nnext\nstep\n");
+        assert_eq!(transcript, "\
+Paused in script at line 1
+   1 | fun factorial(n) {
+(debug) Breakpoint at line 3
+(debug) Paused in factorial at line 3
+   3 |         return 1;
+(debug) #0 factorial at line 3
+#1 script at line 22
+(debug) n = 1
+(debug) 10
+(debug) Paused in script at line 21
+  21 | for (var i = 1; i <= 3; i = i + 1) {
+(debug) Removed the breakpoint at line 3
+(debug) Paused in script at line 22
+  22 |     counter.add(factorial(i));
+(debug) Paused in factorial at line 2
+   2 |     if (n <= 1) {
+(debug) \n9
+");
+    }
+
+    #[test]
+    fn debug_quit() {
+        // Quitting stops the script before it prints anything
+        let transcript = debug("break 15\ncontinue\nprint amount\nquit\n");
+        assert_eq!(transcript, "\
+Paused in script at line 1
+   1 | fun factorial(n) {
+(debug) Breakpoint at line 15
+(debug) Paused in add at line 15
+  15 |         this.count = this.count + amount;
+(debug) 1
+(debug) ");
+    }
+
+    #[test]
+    fn debug_without_commands() {
+        let mut lox = Lox::new();
+        lox.debug_file("./example/debugger/debugger.lox", "".as_bytes()).unwrap();
+    }
+}
@@ -0,0 +1,22 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_destructuring() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/destructuring/destructuring.lox").unwrap();
+    }
+
+    #[test]
+    fn run_deep_recursion() {
+        // The main thread's stack size, which scripts normally run with
+        let child = std::thread::Builder::new().stack_size(8 * 1024 * 1024).spawn(|| {
+            let mut lox = Lox::new();
+            lox.run_file("./example/destructuring/deep_recursion.lox").unwrap();
+        }).unwrap();
+        child.join().unwrap();
+    }
+}
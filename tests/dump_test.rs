@@ -0,0 +1,29 @@
+use rustylox::{Dump, Lox};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump_scopes(dump: Dump) {
+        let mut lox = Lox::new();
+        lox.set_dump(Some(dump));
+        lox.run_file("./example/dump/scopes.lox").unwrap();
+    }
+
+    #[test]
+    fn dump_tokens() {
+        dump_scopes(Dump::Tokens);
+    }
+
+    #[test]
+    fn dump_ast() {
+        dump_scopes(Dump::Ast);
+        dump_scopes(Dump::AstJson);
+    }
+
+    #[test]
+    fn dump_resolved() {
+        dump_scopes(Dump::Resolved);
+        dump_scopes(Dump::ResolvedJson);
+    }
+}
@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_enum() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/enum/enum.lox").unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A line an example should print, from one of its comments.
+    #[derive(Debug)]
+    enum Expected<'a> {
+        // `// expect: output` is the whole line
+        Output(&'a str),
+        // `// expect runtime error: message` and `// expect compile error: message`
+        // only have to be part of the line reporting the error
+        Error(&'a str),
+    }
+
+    impl Expected<'_> {
+        fn matches(&self, line: &str) -> bool {
+            match self {
+                Expected::Output(output) => line == *output,
+                Expected::Error(message) => line.contains(message),
+            }
+        }
+    }
+
+    /// Runs an example and checks that it prints exactly the lines its comments expect.
+    /// Errors the scanner finds are printed to stderr, so with a compile error
+    /// expected stderr is read too, after stdout.
+    fn check_example(path: &Path, args: &[&str]) {
+        let source = std::fs::read_to_string(path).unwrap();
+        let mut expected = Vec::new();
+        let mut compile_error = false;
+        for line in source.lines() {
+            if let Some((_, message)) = line.split_once("// expect runtime error: ") {
+                expected.push(Expected::Error(message));
+            } else if let Some((_, message)) = line.split_once("// expect compile error: ") {
+                expected.push(Expected::Error(message));
+                compile_error = true;
+            } else if let Some((_, output)) = line.split_once("// expect:") {
+                expected.push(Expected::Output(output.strip_prefix(' ').unwrap_or(output)));
+            }
+        }
+
+        let output = Command::new(env!("CARGO_BIN_EXE_rustylox")).args(args).arg(path).output().unwrap();
+        let mut lines: Vec<String> = String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect();
+        if compile_error {
+            assert!(!output.status.success(), "{} ran despite its compile errors", path.display());
+            lines.extend(String::from_utf8(output.stderr).unwrap().lines().map(String::from));
+        }
+
+        let matches = lines.len() == expected.len()
+            && lines.iter().zip(&expected).all(|(line, expected)| expected.matches(line));
+        assert!(matches, "{} printed {:#?}\nbut expected {:#?}", path.display(), lines, expected);
+    }
+
+    /// Checks every example in a directory of `example`.  The directories not
+    /// checked here hold the reference test suite, whose error messages this
+    /// interpreter doesn't share.
+    fn check_examples(directory: &str, args: &[&str]) {
+        let mut paths: Vec<_> = std::fs::read_dir(Path::new("./example").join(directory))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "lox"))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "No examples in {}", directory);
+        paths.iter().for_each(|path| check_example(path, args));
+    }
+
+    #[test]
+    fn check_comments() {
+        check_examples("comments", &[]);
+    }
+
+    #[test]
+    fn check_const() {
+        check_examples("const", &[]);
+    }
+
+    #[test]
+    fn check_debugger() {
+        check_examples("debugger", &[]);
+    }
+
+    #[test]
+    fn check_destructuring() {
+        check_examples("destructuring", &[]);
+    }
+
+    #[test]
+    fn check_dump() {
+        check_examples("dump", &[]);
+    }
+
+    #[test]
+    fn check_enum() {
+        check_examples("enum", &[]);
+    }
+
+    #[test]
+    fn check_for_in() {
+        check_examples("for_in", &[]);
+    }
+
+    #[test]
+    fn check_formatter() {
+        check_examples("formatter", &[]);
+    }
+
+    #[test]
+    fn check_generator() {
+        check_examples("generator", &[]);
+    }
+
+    #[test]
+    fn check_lsp() {
+        check_examples("lsp", &[]);
+    }
+
+    #[test]
+    fn check_match() {
+        check_examples("match", &[]);
+    }
+
+    #[test]
+    fn check_number() {
+        check_examples("number", &[]);
+    }
+
+    #[test]
+    fn check_optimizer() {
+        check_examples("optimizer", &[]);
+        check_examples("optimizer", &["--optimize"]);
+    }
+
+    #[test]
+    fn check_parameters() {
+        check_examples("parameters", &[]);
+    }
+
+    #[test]
+    fn check_private() {
+        check_examples("private", &[]);
+    }
+
+    #[test]
+    fn check_reflection() {
+        check_examples("reflection", &[]);
+    }
+
+    #[test]
+    fn check_resolver() {
+        check_examples("resolver", &[]);
+    }
+
+    #[test]
+    fn check_strict() {
+        check_examples("strict", &["--strict"]);
+    }
+
+    #[test]
+    fn check_tail_call() {
+        check_examples("tail_call", &[]);
+    }
+
+    #[test]
+    fn check_testing() {
+        check_examples("testing", &[]);
+    }
+
+    #[test]
+    fn check_to_string() {
+        check_examples("to_string", &[]);
+    }
+
+    #[test]
+    fn check_traits() {
+        check_examples("traits", &[]);
+    }
+
+    #[test]
+    fn check_warnings() {
+        check_examples("warnings", &[]);
+    }
+}
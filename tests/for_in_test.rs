@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_for_in() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/for_in/for_in.lox").unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_unformatted() {
+        let mut lox = Lox::new();
+        let source = std::fs::read_to_string("./example/formatter/unformatted.lox").unwrap();
+        let formatted = std::fs::read_to_string("./example/formatter/formatted.lox").unwrap();
+        assert_eq!(lox.format(&source), Some(formatted));
+    }
+
+    #[test]
+    fn format_comments() {
+        let mut lox = Lox::new();
+        let source = std::fs::read_to_string("./example/formatter/comments.lox").unwrap();
+        let formatted = std::fs::read_to_string("./example/formatter/comments_formatted.lox").unwrap();
+        assert_eq!(lox.format(&source), Some(formatted));
+    }
+
+    #[test]
+    fn check_formatted() {
+        let mut lox = Lox::new();
+        assert_eq!(lox.format_file("./example/formatter/formatted.lox", true).unwrap(), Some(true));
+    }
+}
@@ -0,0 +1,74 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> String {
+        format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+    }
+
+    fn request(id: usize, method: &str, line: usize, character: usize) -> String {
+        message(&format!(
+            r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{{"textDocument":{{"uri":"file:///greeter.lox"}},"position":{{"line":{},"character":{}}},"context":{{"includeDeclaration":true}}}}}}"#,
+            id, method, line, character
+        ))
+    }
+
+    #[test]
+    fn serve_greeter() {
+        let source = std::fs::read_to_string("./example/lsp/greeter.lox").unwrap();
+        let text = source.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+        let input = [
+            message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#),
+            message(&format!(
+                r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///greeter.lox","text":"{}"}}}}}}"#,
+                text
+            )),
+            request(2, "textDocument/definition", 11, 20),
+            request(3, "textDocument/references", 10, 10),
+            request(4, "textDocument/hover", 12, 12),
+            request(5, "textDocument/completion", 16, 8),
+            request(6, "textDocument/documentSymbol", 0, 0),
+            message(r#"{"jsonrpc":"2.0","id":7,"method":"shutdown"}"#),
+            message(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+        ].concat();
+
+        let mut output = Vec::new();
+        let mut lox = Lox::new();
+        assert!(lox.run_language_server(input.as_bytes(), &mut output).unwrap());
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r#""diagnostics":[]"#));
+        assert!(output.contains(r#""id":2,"result":{"uri":"file:///greeter.lox","range":{"start":{"line":0,"character":6}"#));
+        assert!(output.contains(r#"{"start":{"line":11,"character":26},"end":{"line":11,"character":30}}"#));
+        assert!(output.contains(r#""value":"local variable greeter""#));
+        assert!(output.contains(r#""id":5,"result":[{"label":"init","kind":2},{"label":"greet","kind":2}]"#));
+        assert!(output.contains(r#"{"name":"make","kind":12"#));
+        assert!(output.contains(r#""id":7,"result":null"#));
+    }
+
+    #[test]
+    fn count_columns_in_utf16() {
+        // The emoji takes two UTF-16 code units and the accented letters one each
+        let input = [
+            message(r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///greeter.lox","text":"var s = \"héllo wörld 😀\"; var x = 1; print x;"}}}"#),
+            request(1, "textDocument/definition", 0, 43),
+            message(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+        ].concat();
+
+        let mut output = Vec::new();
+        let mut lox = Lox::new();
+        lox.run_language_server(input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains(r#""id":1,"result":{"uri":"file:///greeter.lox","range":{"start":{"line":0,"character":30},"end":{"line":0,"character":31}}}"#));
+    }
+
+    #[test]
+    fn reject_invalid_content_length() {
+        let input = "Content-Length: twelve\r\n\r\n{}";
+        let mut lox = Lox::new();
+        assert!(lox.run_language_server(input.as_bytes(), Vec::new()).is_err());
+    }
+}
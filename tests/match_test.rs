@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_match() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/match/match.lox").unwrap();
+    }
+}
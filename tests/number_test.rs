@@ -0,0 +1,30 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_integers() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/number/integers.lox").unwrap();
+    }
+
+    #[test]
+    fn run_bignum() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/number/bignum.lox").unwrap();
+    }
+
+    #[test]
+    fn run_literals() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/number/literals.lox").unwrap();
+    }
+
+    #[test]
+    fn run_extended_literals() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/number/extended_literals.lox").unwrap();
+    }
+}
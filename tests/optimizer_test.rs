@@ -0,0 +1,13 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_optimizer() {
+        let mut lox = Lox::new();
+        lox.set_optimize(true);
+        lox.run_file("./example/optimizer/optimizer.lox").unwrap();
+    }
+}
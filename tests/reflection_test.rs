@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_reflection() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/reflection/reflection.lox").unwrap();
+    }
+}
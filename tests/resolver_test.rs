@@ -0,0 +1,22 @@
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_multiple_errors() {
+        let path = "./example/resolver/multiple_errors.lox";
+        let expected: String = std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.split_once("// expect compile error: "))
+            .map(|(_, error)| format!("{}\n", error))
+            .collect();
+
+        let output = Command::new(env!("CARGO_BIN_EXE_rustylox")).arg(path).output().unwrap();
+        assert_eq!(output.status.code(), Some(65));
+        assert_eq!(expected.lines().count(), 5);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+}
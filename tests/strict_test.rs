@@ -0,0 +1,29 @@
+use rustylox::Lox;
+
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_forward_reference() {
+        let mut lox = Lox::new();
+        lox.set_strict(true);
+        lox.run_file("./example/strict/forward_reference.lox").unwrap();
+    }
+
+    #[test]
+    fn report_undefined_globals() {
+        let output = Command::new(env!("CARGO_BIN_EXE_rustylox"))
+            .args(["--strict", "./example/strict/undefined_globals.lox"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(65));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "\
+[line 2] UndefinedGlobal: Undefined variable: mesage
+[line 11] UndefinedGlobal: Undefined variable: cout
+[line 15] UndefinedGlobal: Undefined variable: later
+");
+    }
+}
@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_tail_call() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/tail_call/tail_call.lox").unwrap();
+    }
+}
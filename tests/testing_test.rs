@@ -0,0 +1,26 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_assert() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/testing/assert.lox").unwrap();
+    }
+
+    #[test]
+    fn run_test_blocks() {
+        let mut lox = Lox::new();
+        let passed = lox.run_tests(&[String::from("./example/testing/math.lox")]).unwrap();
+        assert!(passed);
+    }
+
+    #[test]
+    fn fail_tests_after_prelude_error() {
+        let mut lox = Lox::new();
+        let passed = lox.run_tests(&[String::from("./example/testing/prelude_error.lox")]).unwrap();
+        assert!(!passed);
+    }
+}
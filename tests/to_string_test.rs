@@ -0,0 +1,12 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_to_string() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/to_string/to_string.lox").unwrap();
+    }
+}
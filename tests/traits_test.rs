@@ -0,0 +1,18 @@
+use rustylox::Lox;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_traits() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/traits/traits.lox").unwrap();
+    }
+
+    #[test]
+    fn run_local_trait() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/traits/local_trait.lox").unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use rustylox::Lox;
+
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_warnings() {
+        let mut lox = Lox::new();
+        lox.run_file("./example/warnings/warnings.lox").unwrap();
+    }
+
+    #[test]
+    fn report_warnings() {
+        let output = Command::new(env!("CARGO_BIN_EXE_rustylox"))
+            .arg("./example/warnings/warnings.lox")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "\
+[line 1] Warning: Parameter is never used: unused
+[line 2] Warning: Local variable is never read: local
+[line 5] Warning: Shadows a variable declared on line 3: read
+[line 9] Warning: Unreachable code after return: print
+[line 20] Warning: Shadows a variable declared on line 17: total
+[line 21] Warning: Local variable is never read: typo
+");
+    }
+}